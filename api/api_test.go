@@ -2,6 +2,7 @@ package main
 
 import (
 	"encoding/json"
+	"io"
 	"net/http"
 	"net/http/httptest"
 	"testing"
@@ -9,14 +10,40 @@ import (
 	"github.com/gorilla/mux"
 )
 
-func setupRouter() *mux.Router {
+func setupRouter(consensusURL string) *mux.Router {
 	r := mux.NewRouter()
-	registerRoutes(r)
+	registerRoutes(r, newConsensusAPI(consensusURL))
 	return r
 }
 
+// fakeConsensusNode serves handler in place of the consensus node's API.
+func fakeConsensusNode(t *testing.T, handler http.HandlerFunc) *httptest.Server {
+	server := httptest.NewServer(handler)
+	t.Cleanup(server.Close)
+	return server
+}
+
+func getStatus(t *testing.T, consensusURL string) map[string]string {
+	router := setupRouter(consensusURL)
+
+	req := httptest.NewRequest(http.MethodGet, "/status", nil)
+	rr := httptest.NewRecorder()
+
+	router.ServeHTTP(rr, req)
+
+	if rr.Code != http.StatusOK {
+		t.Fatalf("expected status %d got %d", http.StatusOK, rr.Code)
+	}
+
+	var resp map[string]string
+	if err := json.NewDecoder(rr.Body).Decode(&resp); err != nil {
+		t.Fatalf("failed to decode response: %v", err)
+	}
+	return resp
+}
+
 func TestHealthEndpoint(t *testing.T) {
-	router := setupRouter()
+	router := setupRouter("http://127.0.0.1:0")
 
 	req := httptest.NewRequest(http.MethodGet, "/health", nil)
 	rr := httptest.NewRecorder()
@@ -38,26 +65,101 @@ func TestHealthEndpoint(t *testing.T) {
 }
 
 func TestStatusEndpoint(t *testing.T) {
-	router := setupRouter()
+	node := fakeConsensusNode(t, func(w http.ResponseWriter, r *http.Request) {
+		if r.URL.Path != "/round" {
+			http.NotFound(w, r)
+			return
+		}
+		w.Write([]byte(`{"height":7,"round":2,"proposer":"v3"}`))
+	})
 
-	req := httptest.NewRequest(http.MethodGet, "/status", nil)
-	rr := httptest.NewRecorder()
-
-	router.ServeHTTP(rr, req)
+	resp := getStatus(t, node.URL)
 
-	if rr.Code != http.StatusOK {
-		t.Fatalf("expected status %d got %d", http.StatusOK, rr.Code)
+	expected := map[string]string{
+		"network_status": "running",
+		"height":         "7",
+		"round":          "2",
+		"proposer":       "v3",
 	}
+	for key, value := range expected {
+		if resp[key] != value {
+			t.Errorf("expected %s '%s' got '%s'", key, value, resp[key])
+		}
+	}
+	if resp["description"] == "" {
+		t.Error("description should not be empty")
+	}
+}
 
-	var resp map[string]string
-	if err := json.NewDecoder(rr.Body).Decode(&resp); err != nil {
-		t.Fatalf("failed to decode response: %v", err)
+func TestStatusEndpointWithoutProposer(t *testing.T) {
+	node := fakeConsensusNode(t, func(w http.ResponseWriter, r *http.Request) {
+		w.Write([]byte(`{"height":0,"round":0,"proposer":null}`))
+	})
+
+	resp := getStatus(t, node.URL)
+
+	if resp["network_status"] != "running" {
+		t.Errorf("expected network_status 'running' got '%s'", resp["network_status"])
+	}
+	if _, ok := resp["proposer"]; ok {
+		t.Errorf("expected no proposer got '%s'", resp["proposer"])
 	}
+}
 
-	if resp["network_status"] == "" {
-		t.Error("network_status should not be empty")
+func TestStatusEndpointReportsUnreachableNode(t *testing.T) {
+	node := httptest.NewServer(http.NotFoundHandler())
+	node.Close()
+
+	resp := getStatus(t, node.URL)
+
+	if resp["network_status"] != "unreachable" {
+		t.Errorf("expected network_status 'unreachable' got '%s'", resp["network_status"])
 	}
 	if resp["description"] == "" {
 		t.Error("description should not be empty")
 	}
 }
+
+func TestConsensusProxyForwardsPathAndQuery(t *testing.T) {
+	var path, query string
+	node := fakeConsensusNode(t, func(w http.ResponseWriter, r *http.Request) {
+		path, query = r.URL.Path, r.URL.RawQuery
+		w.WriteHeader(http.StatusTeapot)
+		w.Write([]byte(`{"height":1}`))
+	})
+	router := setupRouter(node.URL)
+
+	req := httptest.NewRequest(http.MethodGet, "/consensus/blocks/1?verbose=true&limit=2", nil)
+	rr := httptest.NewRecorder()
+
+	router.ServeHTTP(rr, req)
+
+	if path != "/blocks/1" {
+		t.Errorf("expected path '/blocks/1' got '%s'", path)
+	}
+	if query != "verbose=true&limit=2" {
+		t.Errorf("expected query 'verbose=true&limit=2' got '%s'", query)
+	}
+	if rr.Code != http.StatusTeapot {
+		t.Errorf("expected status %d got %d", http.StatusTeapot, rr.Code)
+	}
+	body, _ := io.ReadAll(rr.Body)
+	if string(body) != `{"height":1}` {
+		t.Errorf("expected the node's body got '%s'", body)
+	}
+}
+
+func TestConsensusProxyReportsUnreachableNode(t *testing.T) {
+	node := httptest.NewServer(http.NotFoundHandler())
+	node.Close()
+	router := setupRouter(node.URL)
+
+	req := httptest.NewRequest(http.MethodGet, "/consensus/head", nil)
+	rr := httptest.NewRecorder()
+
+	router.ServeHTTP(rr, req)
+
+	if rr.Code != http.StatusBadGateway {
+		t.Errorf("expected status %d got %d", http.StatusBadGateway, rr.Code)
+	}
+}
@@ -16,20 +16,39 @@
  import (
 	 "encoding/json"
 	 "fmt"
+	 "io"
 	 "log"
 	 "math/rand"
 	 "net/http"
+	 "strconv"
+	 "strings"
 	 "time"
  
 	 "github.com/gorilla/mux"
  )
  
- // registerRoutes wires up our HTTP routes.
- func registerRoutes(r *mux.Router) {
+ // registerRoutes wires up our HTTP routes; consensus reaches the consensus node's API.
+ func registerRoutes(r *mux.Router, consensus *ConsensusAPI) {
 	 r.HandleFunc("/health", healthCheckHandler).Methods("GET")
 	 r.HandleFunc("/start-node", startNodeHandler).Methods("POST")
 	 r.HandleFunc("/deploy-contract", deployContractHandler).Methods("POST")
-	 r.HandleFunc("/status", statusHandler).Methods("GET")
+	 r.HandleFunc("/status", consensus.statusHandler).Methods("GET")
+	 r.PathPrefix("/consensus/").HandlerFunc(consensus.proxyHandler).Methods("GET")
+ }
+ 
+ // ConsensusAPI queries the API of a consensus node.
+ type ConsensusAPI struct {
+	 BaseURL string
+	 Client  *http.Client
+ }
+ 
+ // newConsensusAPI returns a client of the consensus node API at baseURL; the short timeout keeps
+ // /status responsive when the node is down.
+ func newConsensusAPI(baseURL string) *ConsensusAPI {
+	 return &ConsensusAPI{
+		 BaseURL: baseURL,
+		 Client:  &http.Client{Timeout: 2 * time.Second},
+	 }
  }
  
  // HealthCheckResponse represents a basic health check JSON.
@@ -99,19 +118,67 @@
 	 json.NewEncoder(w).Encode(response)
  }
  
- // statusHandler returns a mock status of the entire system.
- func statusHandler(w http.ResponseWriter, r *http.Request) {
+ // RoundState mirrors the consensus node's /round response.
+ type RoundState struct {
+	 Height   uint64  `json:"height"`
+	 Round    uint32  `json:"round"`
+	 Proposer *string `json:"proposer"`
+ }
+ 
+ // fetch decodes the JSON answer of the consensus node's API at path into out.
+ func (c *ConsensusAPI) fetch(path string, out interface{}) error {
+	 resp, err := c.Client.Get(c.BaseURL + path)
+	 if err != nil {
+		 return err
+	 }
+	 defer resp.Body.Close()
+	 if resp.StatusCode != http.StatusOK {
+		 return fmt.Errorf("consensus node answered %s", resp.Status)
+	 }
+	 return json.NewDecoder(resp.Body).Decode(out)
+ }
+ 
+ // statusHandler reports the state of the consensus network, as seen by the consensus node.
+ func (c *ConsensusAPI) statusHandler(w http.ResponseWriter, r *http.Request) {
 	 w.Header().Set("Content-Type", "application/json")
  
-	 // Stub data: random "uptime" or "synced" state
-	 rand.Seed(time.Now().UnixNano())
-	 statuses := []string{"running", "synced", "pending", "maintenance"}
-	 currentStatus := statuses[rand.Intn(len(statuses))]
+	 var round RoundState
+	 if err := c.fetch("/round", &round); err != nil {
+		 log.Printf("Consensus node unavailable: %v", err)
+		 json.NewEncoder(w).Encode(map[string]string{
+			 "network_status": "unreachable",
+			 "description":    fmt.Sprintf("Consensus node at %s did not answer", c.BaseURL),
+		 })
+		 return
+	 }
  
 	 response := map[string]string{
-		 "network_status": currentStatus,
-		 "description":    "Demo status endpoint for the PeoChain network",
+		 "network_status": "running",
+		 "description":    "Status of the PeoChain consensus network",
+		 "height":         strconv.FormatUint(round.Height, 10),
+		 "round":          strconv.FormatUint(uint64(round.Round), 10),
+	 }
+	 if round.Proposer != nil {
+		 response["proposer"] = *round.Proposer
 	 }
 	 json.NewEncoder(w).Encode(response)
  }
- 
\ No newline at end of file
+ 
+ // proxyHandler forwards /consensus/<path>, with its query string, to the consensus node's API,
+ // e.g. /consensus/head, /consensus/blocks/1 or /consensus/validators.
+ func (c *ConsensusAPI) proxyHandler(w http.ResponseWriter, r *http.Request) {
+	 target := c.BaseURL + strings.TrimPrefix(r.URL.Path, "/consensus")
+	 if r.URL.RawQuery != "" {
+		 target += "?" + r.URL.RawQuery
+	 }
+	 resp, err := c.Client.Get(target)
+	 if err != nil {
+		 http.Error(w, fmt.Sprintf("Consensus node unavailable: %v", err), http.StatusBadGateway)
+		 return
+	 }
+	 defer resp.Body.Close()
+ 
+	 w.Header().Set("Content-Type", "application/json")
+	 w.WriteHeader(resp.StatusCode)
+	 io.Copy(w, resp.Body)
+ }
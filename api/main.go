@@ -33,8 +33,9 @@
 	 // Create a new mux router.
 	 r := mux.NewRouter()
  
-	 // Register routes from handlers.go
-	 registerRoutes(r)
+	 // Register routes from handlers.go, backed by the consensus node's API.
+	 consensus := newConsensusAPI(getEnvVarOrDefault("CONSENSUS_RPC_URL", "http://consensus-node:8545"))
+	 registerRoutes(r, consensus)
  
 	 // Start the HTTP server.
 	 log.Printf("API service listening on port %s", port)
@@ -0,0 +1,276 @@
+// src/analysis.rs
+
+use crate::genesis::Genesis;
+use crate::{ConsensusEngine, ConsensusError};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// How a validator fared across the runs of a [`MonteCarlo`] analysis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStats {
+    /// Identifier of the validator.
+    pub id: String,
+    /// Stake of the validator.
+    pub stake: u64,
+    /// Whether the validator was malicious.
+    pub malicious: bool,
+    /// Share of the total stake held by the validator.
+    pub stake_share: f64,
+    /// Blocks the validator proposed, over all runs.
+    pub proposed: u64,
+    /// Blocks of the validator that were finalized, over all runs.
+    pub accepted: u64,
+    /// Share of all proposals made by the validator.
+    pub proposal_share: f64,
+    /// Share of the validator's proposals that were finalized, or 0 if it never proposed.
+    pub acceptance_rate: f64,
+    /// Synergy score of the validator after each round, averaged over the runs.
+    pub score_trajectory: Vec<f64>,
+}
+
+/// The outcome of a [`MonteCarlo`] analysis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    /// Number of simulations run.
+    pub runs: u64,
+    /// Consensus rounds of every simulation.
+    pub rounds: u64,
+    /// Seed of the first simulation; run `i` is seeded with `seed + i`.
+    pub seed: u64,
+    /// Per-validator statistics, in the order of the validator set.
+    pub validators: Vec<ValidatorStats>,
+    /// Gini coefficient of the proposal counts: 0 when every validator proposes equally often,
+    /// approaching 1 when one validator makes every proposal.
+    pub gini: f64,
+    /// Nakamoto coefficient of the proposal counts: the fewest validators that together make
+    /// more than a third of the proposals, enough to stall a BFT network.
+    pub nakamoto: usize,
+}
+
+impl MonteCarloReport {
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports are always serializable")
+    }
+
+    /// Returns the per-validator statistics as CSV, one row per validator, without the score
+    /// trajectories.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "id,stake,malicious,stake_share,proposed,accepted,proposal_share,acceptance_rate,\
+             final_score\n",
+        );
+        for v in &self.validators {
+            let final_score = v.score_trajectory.last().copied().unwrap_or(0.0);
+            writeln!(
+                csv,
+                "{},{},{},{:.6},{},{},{:.6},{:.6},{:.6}",
+                v.id,
+                v.stake,
+                v.malicious,
+                v.stake_share,
+                v.proposed,
+                v.accepted,
+                v.proposal_share,
+                v.acceptance_rate,
+                final_score
+            )
+            .expect("writing to a string cannot fail");
+        }
+        csv
+    }
+
+    /// Returns the score trajectories as CSV, one row per round with a column per validator.
+    pub fn trajectories_csv(&self) -> String {
+        let mut csv = "round".to_string();
+        for v in &self.validators {
+            write!(csv, ",{}", v.id).expect("writing to a string cannot fail");
+        }
+        csv.push('\n');
+        for round in 0..self.rounds as usize {
+            write!(csv, "{}", round + 1).expect("writing to a string cannot fail");
+            for v in &self.validators {
+                write!(csv, ",{:.6}", v.score_trajectory[round])
+                    .expect("writing to a string cannot fail");
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Runs many seeded simulations of a genesis validator set to measure how fairly proposals are
+/// spread.
+///
+/// Each run builds a fresh [`Network`](crate::Network) from the genesis, with its scoring
+/// parameters and epoch length, seeded with `seed + run`, and runs it for `rounds` consensus
+/// rounds. The report answers how often each validator proposes
+/// relative to its stake, how many of its blocks are accepted and how its synergy score evolves,
+/// which shows whether malicious validators get pushed out.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::analysis::MonteCarlo;
+/// use peo_consensus::genesis::Genesis;
+///
+/// let genesis = Genesis::from_toml_str(
+///     r#"
+///     chain_id = "peochain-analysis"
+///
+///     [[validators]]
+///     id = "v1"
+///     stake = 1000
+///
+///     [[validators]]
+///     id = "v2"
+///     stake = 1000
+///
+///     [[validators]]
+///     id = "v3"
+///     stake = 800
+///     malicious = true
+///     "#,
+/// )
+/// .unwrap();
+/// let report = MonteCarlo { runs: 10, rounds: 50, seed: 1 }.run(&genesis).unwrap();
+/// let malicious = &report.validators[2];
+/// assert_eq!(malicious.acceptance_rate, 0.0);
+/// assert!(malicious.score_trajectory[49] < 0.0);
+/// assert_eq!(report.validators[0].acceptance_rate, 1.0);
+/// assert_eq!(report.validators[0].score_trajectory.len(), 50);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonteCarlo {
+    /// Number of simulations.
+    pub runs: u64,
+    /// Consensus rounds of every simulation.
+    pub rounds: u64,
+    /// Seed of the first simulation.
+    pub seed: u64,
+}
+
+impl MonteCarlo {
+    /// Runs the simulations of the validator set of `genesis` and aggregates their outcomes.
+    pub fn run(&self, genesis: &Genesis) -> Result<MonteCarloReport, ConsensusError> {
+        genesis.validate()?;
+        let set = &genesis.validators;
+        let count = set.len();
+        let mut proposed = vec![0u64; count];
+        let mut accepted = vec![0u64; count];
+        let mut scores = vec![vec![0.0; count]; self.rounds as usize];
+        for run in 0..self.runs {
+            let mut network = genesis.network(Some(self.seed.wrapping_add(run)))?;
+            for totals in scores.iter_mut() {
+                network.run_consensus_round()?;
+                for (i, spec) in set.iter().enumerate() {
+                    if let Some(engine) = network
+                        .validators
+                        .iter()
+                        .find(|v| v.validator_id() == spec.id)
+                    {
+                        totals[i] += engine.get_synergy_score();
+                    }
+                }
+            }
+            for (i, spec) in set.iter().enumerate() {
+                if let Some(engine) = network
+                    .validators
+                    .iter()
+                    .find(|v| v.validator_id() == spec.id)
+                {
+                    proposed[i] += engine.proposed_blocks();
+                    accepted[i] += engine.accepted_blocks();
+                }
+            }
+        }
+
+        let total_stake: u64 = set.iter().map(|v| v.stake).sum();
+        let total_proposed: u64 = proposed.iter().sum();
+        let share = |part: u64, whole: u64| {
+            if whole == 0 {
+                0.0
+            } else {
+                part as f64 / whole as f64
+            }
+        };
+        let validators = set
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| ValidatorStats {
+                id: spec.id.clone(),
+                stake: spec.stake,
+                malicious: spec.malicious,
+                stake_share: share(spec.stake, total_stake),
+                proposed: proposed[i],
+                accepted: accepted[i],
+                proposal_share: share(proposed[i], total_proposed),
+                acceptance_rate: share(accepted[i], proposed[i]),
+                score_trajectory: scores
+                    .iter()
+                    .map(|totals| totals[i] / self.runs.max(1) as f64)
+                    .collect(),
+            })
+            .collect();
+        Ok(MonteCarloReport {
+            runs: self.runs,
+            rounds: self.rounds,
+            seed: self.seed,
+            validators,
+            gini: gini(&proposed),
+            nakamoto: nakamoto(&proposed),
+        })
+    }
+}
+
+/// Returns the Gini coefficient of `values`: 0 for perfect equality, approaching 1 as a single
+/// value holds the whole total.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::analysis::gini;
+///
+/// assert_eq!(gini(&[5, 5, 5, 5]), 0.0);
+/// assert_eq!(gini(&[0, 0, 0, 8]), 0.75);
+/// ```
+pub fn gini(values: &[u64]) -> f64 {
+    let total: u64 = values.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as f64;
+    let weighted: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| (2.0 * (i as f64 + 1.0) - n - 1.0) * x as f64)
+        .sum();
+    weighted / (n * total as f64)
+}
+
+/// Returns the Nakamoto coefficient of `values`: the fewest entries that together hold more
+/// than a third of the total, or 0 if the total is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::analysis::nakamoto;
+///
+/// assert_eq!(nakamoto(&[10, 10, 10, 10, 10, 10]), 3);
+/// assert_eq!(nakamoto(&[50, 10, 10, 10]), 1);
+/// ```
+pub fn nakamoto(values: &[u64]) -> usize {
+    let total: u64 = values.iter().sum();
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let mut held = 0;
+    for (i, value) in sorted.into_iter().enumerate() {
+        held += value;
+        if held * 3 > total {
+            return i + 1;
+        }
+    }
+    0
+}
@@ -0,0 +1,348 @@
+// src/block.rs
+
+use crate::crypto::{decode_hex32, Signature, SigningKey, ValidatorRegistry};
+use crate::transaction::Transaction;
+use crate::ConsensusError;
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Domain prefix of the message a proposer signs, so a proposal signature can never be replayed
+/// as a signature over anything else.
+const PROPOSAL_DOMAIN: &[u8] = b"peochain/proposal";
+
+/// Domain prefix of the message a proposer signs to bind a proposal to the round it is made in.
+const SIGNED_HEADER_DOMAIN: &[u8] = b"peochain/signed-header";
+
+/// A 32-byte SHA-256 digest used to identify blocks and commit to their contents.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::Hash;
+///
+/// let hash = Hash::digest(b"peochain");
+/// assert_eq!(Hash::from_hex(&hash.to_hex()), Some(hash));
+/// assert_ne!(hash, Hash::ZERO);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hash(pub [u8; 32]);
+
+impl Hash {
+    /// The all-zero hash, used as the parent of the genesis block.
+    pub const ZERO: Hash = Hash([0; 32]);
+
+    /// Computes the SHA-256 digest of `data`.
+    pub fn digest(data: &[u8]) -> Self {
+        Hash(Sha256::digest(data).into())
+    }
+
+    /// Returns the raw bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the lowercase hexadecimal representation of the hash.
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Parses a 64-character hexadecimal string, returning `None` if it is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::Hash;
+    ///
+    /// let hash = Hash::digest(b"block");
+    /// assert_eq!(Hash::from_hex(&hash.to_hex()), Some(hash));
+    /// assert_eq!(Hash::from_hex(&"+0".repeat(32)), None);
+    /// ```
+    pub fn from_hex(s: &str) -> Option<Self> {
+        decode_hex32(s).map(Hash)
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Serialize for Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Hash::from_hex(&s)
+            .ok_or_else(|| serde::de::Error::custom("expected a 64-character hex hash"))
+    }
+}
+
+impl fmt::Debug for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hash({})", self.to_hex())
+    }
+}
+
+/// The header of a block, linking it to its parent and committing to its contents.
+///
+/// The header is the only part of a block that is hashed; the transactions are bound to it
+/// through `transactions_root`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    /// Distance from the genesis block, which has height 0.
+    pub height: u64,
+    /// Hash of the parent block's header.
+    pub parent_hash: Hash,
+    /// Time the block was proposed, in milliseconds.
+    pub timestamp: u64,
+    /// Identifier of the validator that proposed the block.
+    pub proposer: String,
+    /// Merkle root of the block's transactions.
+    pub transactions_root: Hash,
+    /// Root of the account state after applying the block, see
+    /// [`AccountState::root`](crate::state::AccountState::root).
+    pub state_root: Hash,
+}
+
+impl BlockHeader {
+    /// Returns the header of the genesis block shared by every validator.
+    pub fn genesis() -> Self {
+        Self {
+            height: 0,
+            parent_hash: Hash::ZERO,
+            timestamp: 0,
+            proposer: String::new(),
+            transactions_root: transactions_root(&[]),
+            state_root: Hash::ZERO,
+        }
+    }
+
+    /// Returns the canonical binary encoding of the header.
+    ///
+    /// Integers are big-endian and the proposer is length-prefixed, so two different headers
+    /// never share an encoding.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + 32 + 8 + 4 + self.proposer.len() + 32 + 32);
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(self.parent_hash.as_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&(self.proposer.len() as u32).to_be_bytes());
+        out.extend_from_slice(self.proposer.as_bytes());
+        out.extend_from_slice(self.transactions_root.as_bytes());
+        out.extend_from_slice(self.state_root.as_bytes());
+        out
+    }
+
+    /// Computes the canonical hash of the header, which identifies the block.
+    pub fn hash(&self) -> Hash {
+        Hash::digest(&self.encode())
+    }
+}
+
+/// Represents a block in the PeoChain blockchain.
+///
+/// A block consists of a header linking it to its parent, a body of transactions and the
+/// proposer's signature over the header hash.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::{Block, BlockHeader};
+/// use peo_consensus::transaction::{Transaction, TransactionKind};
+///
+/// let genesis = Block::genesis();
+/// let kind = TransactionKind::Transfer { to: "bob".to_string(), amount: 5 };
+/// let tx = Transaction::new("alice".to_string(), 0, kind, 1);
+/// let mut block = Block::new(&genesis.header, "validator1".to_string(), 1_000, vec![tx]);
+/// assert_eq!(block.header.height, 1);
+/// assert_eq!(block.header.parent_hash, genesis.hash());
+/// assert_eq!(block.header.proposer, "validator1");
+/// assert_eq!(block.transactions[0].sender, "alice");
+/// assert!(block.verify_contents().is_ok());
+/// block.transactions[0].fee = 2;
+/// assert!(block.verify_contents().is_err());
+/// block.transactions[0].fee = 1;
+/// assert!(block.signature.is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    /// The block header.
+    pub header: BlockHeader,
+    /// List of transactions included in the block.
+    pub transactions: Vec<Transaction>,
+    /// The proposer's signature over the block hash, if the block has been signed.
+    pub signature: Option<Signature>,
+}
+
+impl Block {
+    /// Returns the genesis block.
+    pub fn genesis() -> Self {
+        Self {
+            header: BlockHeader::genesis(),
+            transactions: vec![],
+            signature: None,
+        }
+    }
+
+    /// Builds a block on top of `parent`, computing its transactions root.
+    ///
+    /// The block carries its parent's state root until the proposer sets the root of the account
+    /// state its transactions lead to, before signing it.
+    pub fn new(
+        parent: &BlockHeader,
+        proposer: String,
+        timestamp: u64,
+        transactions: Vec<Transaction>,
+    ) -> Self {
+        let tx_root = transactions_root(&transactions);
+        Self {
+            header: BlockHeader {
+                height: parent.height + 1,
+                parent_hash: parent.hash(),
+                timestamp,
+                proposer,
+                transactions_root: tx_root,
+                state_root: parent.state_root,
+            },
+            transactions,
+            signature: None,
+        }
+    }
+
+    /// Returns the hash of the block's header.
+    pub fn hash(&self) -> Hash {
+        self.header.hash()
+    }
+
+    /// Checks that the header's transactions root commits to the block's transactions.
+    pub fn verify_contents(&self) -> Result<(), ConsensusError> {
+        if transactions_root(&self.transactions) != self.header.transactions_root {
+            return Err(ConsensusError::InvalidTransactionsRoot);
+        }
+        Ok(())
+    }
+
+    /// Returns the message a proposer signs: a domain prefix followed by the block hash.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        [PROPOSAL_DOMAIN, self.hash().as_bytes()].concat()
+    }
+
+    /// Signs the block with the proposer's key.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = Some(key.sign(&self.signing_bytes()));
+    }
+
+    /// Checks that the block is signed by the key registered for its proposer.
+    pub fn verify_signature(&self, registry: &ValidatorRegistry) -> Result<(), ConsensusError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(ConsensusError::InvalidSignature)?;
+        registry.verify(&self.header.proposer, &self.signing_bytes(), signature)
+    }
+}
+
+/// A proposer's signed statement that it proposed the block with `header` in `round`.
+///
+/// A block's own signature does not cover the round, and a proposer may legitimately propose a
+/// different block at the same height in a later round. Two signed headers for different blocks
+/// at the same height and round, however, prove the proposer equivocated.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::block::SignedHeader;
+/// use peo_consensus::crypto::dev_signing_key;
+/// use peo_consensus::{Block, ValidatorRegistry};
+///
+/// let key = dev_signing_key("v1");
+/// let mut registry = ValidatorRegistry::new();
+/// registry.register("v1".to_string(), key.verifying_key());
+/// let block = Block::new(&Block::genesis().header, "v1".to_string(), 1_000, vec![]);
+///
+/// let signed = SignedHeader::new(block.header.clone(), 2, &key);
+/// assert!(signed.verify(&registry).is_ok());
+/// let mut moved = signed.clone();
+/// moved.round = 3;
+/// assert!(moved.verify(&registry).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHeader {
+    /// Round the block was proposed in.
+    pub round: u32,
+    /// Header of the proposed block.
+    pub header: BlockHeader,
+    /// The proposer's signature over the round and the header hash.
+    pub signature: Signature,
+}
+
+impl SignedHeader {
+    /// Signs `header` as proposed in `round` with the proposer's key.
+    pub fn new(header: BlockHeader, round: u32, key: &SigningKey) -> Self {
+        let signature = key.sign(&Self::signing_bytes(&header, round));
+        Self {
+            round,
+            header,
+            signature,
+        }
+    }
+
+    /// Returns the message a proposer signs: a domain prefix, the round and the header hash.
+    pub fn signing_bytes(header: &BlockHeader, round: u32) -> Vec<u8> {
+        [
+            SIGNED_HEADER_DOMAIN,
+            &round.to_be_bytes(),
+            header.hash().as_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Checks that the header is signed by the key registered for its proposer.
+    pub fn verify(&self, registry: &ValidatorRegistry) -> Result<(), ConsensusError> {
+        let message = Self::signing_bytes(&self.header, self.round);
+        registry.verify(&self.header.proposer, &message, &self.signature)
+    }
+}
+
+/// Computes the Merkle root of a list of transactions.
+///
+/// Leaves are the canonical encodings of the transactions. Leaves and inner nodes are
+/// domain-separated, and an unpaired node is promoted to the next level unchanged. The root of an
+/// empty list is [`Hash::ZERO`].
+pub fn transactions_root(transactions: &[Transaction]) -> Hash {
+    if transactions.is_empty() {
+        return Hash::ZERO;
+    }
+
+    let mut level: Vec<Hash> = transactions
+        .iter()
+        .map(|tx| hash_parts(&[&[0x00], &tx.encode()]))
+        .collect();
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_parts(&[&[0x01], left.as_bytes(), right.as_bytes()]),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+fn hash_parts(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Hash(hasher.finalize().into())
+}
@@ -0,0 +1,130 @@
+// src/crypto.rs
+
+use crate::ConsensusError;
+use ed25519_dalek::Verifier;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+pub use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+/// Maps validator identifiers to the public keys their proposals and votes must be signed with.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::{SigningKey, ValidatorRegistry};
+/// use ed25519_dalek::Signer;
+///
+/// let key = SigningKey::from_bytes(&[1; 32]);
+/// let mut registry = ValidatorRegistry::new();
+/// registry.register("v1".to_string(), key.verifying_key());
+///
+/// let signature = key.sign(b"message");
+/// assert!(registry.verify("v1", b"message", &signature).is_ok());
+/// assert!(registry.verify("v1", b"forged", &signature).is_err());
+/// assert!(registry.verify("v2", b"message", &signature).is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ValidatorRegistry {
+    keys: HashMap<String, VerifyingKey>,
+}
+
+impl ValidatorRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `key` for `validator_id`, returning the key it replaced, if any.
+    pub fn register(&mut self, validator_id: String, key: VerifyingKey) -> Option<VerifyingKey> {
+        self.keys.insert(validator_id, key)
+    }
+
+    /// Removes the key registered for `validator_id`.
+    pub fn remove(&mut self, validator_id: &str) -> Option<VerifyingKey> {
+        self.keys.remove(validator_id)
+    }
+
+    /// Returns the key registered for `validator_id`.
+    pub fn get(&self, validator_id: &str) -> Option<&VerifyingKey> {
+        self.keys.get(validator_id)
+    }
+
+    /// Returns whether a key is registered for `validator_id`.
+    pub fn contains(&self, validator_id: &str) -> bool {
+        self.keys.contains_key(validator_id)
+    }
+
+    /// Checks that `signature` over `message` was made by the key registered for `validator_id`.
+    pub fn verify(
+        &self,
+        validator_id: &str,
+        message: &[u8],
+        signature: &Signature,
+    ) -> Result<(), ConsensusError> {
+        let key = self
+            .get(validator_id)
+            .ok_or(ConsensusError::UnknownValidator)?;
+        key.verify(message, signature)
+            .map_err(|_| ConsensusError::InvalidSignature)
+    }
+}
+
+/// Derives a deterministic signing key from `validator_id`.
+///
+/// Every process of a development network can compute every validator's key from its identifier
+/// alone, so no key material needs to be distributed. Anyone can derive these keys too: never use
+/// them outside a devnet.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::dev_signing_key;
+///
+/// assert_eq!(dev_signing_key("v1").to_bytes(), dev_signing_key("v1").to_bytes());
+/// assert_ne!(dev_signing_key("v1").to_bytes(), dev_signing_key("v2").to_bytes());
+/// ```
+pub fn dev_signing_key(validator_id: &str) -> SigningKey {
+    let seed: [u8; 32] = Sha256::new()
+        .chain_update(b"peochain/dev-key/")
+        .chain_update(validator_id.as_bytes())
+        .finalize()
+        .into();
+    SigningKey::from_bytes(&seed)
+}
+
+/// Decodes a key from 64 hexadecimal characters, returning `None` if it is malformed.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::{decode_key, dev_signing_key, encode_key};
+///
+/// let key = dev_signing_key("v1").verifying_key().to_bytes();
+/// assert_eq!(decode_key(&encode_key(&key)), Some(key));
+/// assert_eq!(decode_key("not a key"), None);
+/// assert_eq!(decode_key(&"+f".repeat(32)), None);
+/// ```
+pub fn decode_key(hex: &str) -> Option<[u8; 32]> {
+    decode_hex32(hex)
+}
+
+/// Decodes exactly 64 hexadecimal digits of either case into 32 bytes.
+///
+/// Every character is checked to be a digit up front, since [`u8::from_str_radix`] alone would
+/// also accept a sign.
+pub(crate) fn decode_hex32(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Returns the lowercase hexadecimal encoding of a key.
+pub fn encode_key(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,219 @@
+// src/delegation.rs
+
+use crate::slashing::distribute_pro_rata;
+use crate::ConsensusError;
+use std::collections::BTreeMap;
+
+/// Stake that a delegator has entrusted to a validator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delegation {
+    /// Account that owns the stake.
+    pub delegator: String,
+    /// Validator the stake is delegated to.
+    pub validator: String,
+    /// Amount of stake delegated.
+    pub amount: u64,
+}
+
+/// Records every delegation in the network.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::delegation::DelegationBook;
+///
+/// let mut book = DelegationBook::new();
+/// book.delegate("alice", "v1", 300).unwrap();
+/// book.delegate("bob", "v1", 100).unwrap();
+/// book.delegate("alice", "v1", 200).unwrap();
+/// assert_eq!(book.delegated_to("v1"), 600);
+///
+/// book.undelegate("alice", "v1", 500).unwrap();
+/// assert_eq!(book.delegations_to("v1").len(), 1);
+/// assert!(book.undelegate("bob", "v1", 101).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DelegationBook {
+    // Keyed by (validator, delegator) so a validator's delegations are contiguous.
+    amounts: BTreeMap<(String, String), u64>,
+}
+
+impl DelegationBook {
+    /// Creates an empty book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `amount` to the stake `delegator` has delegated to `validator`.
+    pub fn delegate(
+        &mut self,
+        delegator: &str,
+        validator: &str,
+        amount: u64,
+    ) -> Result<(), ConsensusError> {
+        if amount == 0 {
+            return Err(ConsensusError::InvalidStake);
+        }
+        *self
+            .amounts
+            .entry((validator.to_string(), delegator.to_string()))
+            .or_insert(0) += amount;
+        Ok(())
+    }
+
+    /// Withdraws `amount` of the stake `delegator` has delegated to `validator`.
+    pub fn undelegate(
+        &mut self,
+        delegator: &str,
+        validator: &str,
+        amount: u64,
+    ) -> Result<(), ConsensusError> {
+        let key = (validator.to_string(), delegator.to_string());
+        let delegated = self
+            .amounts
+            .get_mut(&key)
+            .ok_or(ConsensusError::InvalidStake)?;
+        if amount == 0 || amount > *delegated {
+            return Err(ConsensusError::InvalidStake);
+        }
+        *delegated -= amount;
+        if *delegated == 0 {
+            self.amounts.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Returns the stake `delegator` has delegated to `validator`.
+    pub fn delegated(&self, delegator: &str, validator: &str) -> u64 {
+        self.amounts
+            .get(&(validator.to_string(), delegator.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns every delegation, ordered by validator and then by delegator.
+    pub fn all(&self) -> Vec<Delegation> {
+        self.amounts
+            .iter()
+            .map(|((v, d), amount)| Delegation {
+                delegator: d.clone(),
+                validator: v.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    /// Returns the total stake delegated to `validator`.
+    pub fn delegated_to(&self, validator: &str) -> u64 {
+        self.delegations_to(validator)
+            .iter()
+            .map(|d| d.amount)
+            .sum()
+    }
+
+    /// Returns the delegations to `validator`, ordered by delegator.
+    pub fn delegations_to(&self, validator: &str) -> Vec<Delegation> {
+        self.amounts
+            .iter()
+            .filter(|((v, _), _)| v == validator)
+            .map(|((v, d), amount)| Delegation {
+                delegator: d.clone(),
+                validator: v.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    /// Returns every delegation made by `delegator`.
+    pub fn delegations_of(&self, delegator: &str) -> Vec<Delegation> {
+        self.amounts
+            .iter()
+            .filter(|((_, d), _)| d == delegator)
+            .map(|((v, d), amount)| Delegation {
+                delegator: d.clone(),
+                validator: v.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    /// Takes `fraction` of every delegation to `validator`, rounded down per delegation, and
+    /// returns the total taken.
+    pub fn slash(&mut self, validator: &str, fraction: f64) -> u64 {
+        let mut taken = 0;
+        for ((v, _), amount) in self.amounts.iter_mut() {
+            if v == validator {
+                let cut = (*amount as f64 * fraction.clamp(0.0, 1.0)).floor() as u64;
+                *amount -= cut;
+                taken += cut;
+            }
+        }
+        self.amounts.retain(|_, amount| *amount > 0);
+        taken
+    }
+
+    /// Removes and returns every delegation to `validator`.
+    pub fn remove_validator(&mut self, validator: &str) -> Vec<Delegation> {
+        let removed = self.delegations_to(validator);
+        self.amounts.retain(|(v, _), _| v != validator);
+        removed
+    }
+}
+
+/// How a reward earned by a validator is shared with its delegators.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RewardSplit {
+    /// Amount kept by the validator: its commission plus the share earned by its own stake.
+    pub validator: u64,
+    /// The commission part of `validator`.
+    pub commission: u64,
+    /// Amount paid to each delegator, ordered by delegator.
+    pub delegators: Vec<(String, u64)>,
+}
+
+/// Splits `reward` between a validator and its delegators.
+///
+/// The validator first takes `commission_rate` of the reward, rounded down. The rest is shared in
+/// proportion to stake between the validator's own stake and each delegation, so the parts always
+/// add up to `reward`.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::delegation::{split_reward, Delegation};
+///
+/// let delegations = vec![Delegation {
+///     delegator: "alice".to_string(),
+///     validator: "v1".to_string(),
+///     amount: 1000,
+/// }];
+/// let split = split_reward(100, 1000, 0.1, &delegations);
+/// assert_eq!(split.commission, 10);
+/// assert_eq!(split.validator, 55);
+/// assert_eq!(split.delegators, vec![("alice".to_string(), 45)]);
+/// ```
+pub fn split_reward(
+    reward: u64,
+    self_stake: u64,
+    commission_rate: f64,
+    delegations: &[Delegation],
+) -> RewardSplit {
+    let commission = (reward as f64 * commission_rate.clamp(0.0, 1.0)).floor() as u64;
+    let distributable = reward - commission;
+
+    let stakes: Vec<u64> = std::iter::once(self_stake)
+        .chain(delegations.iter().map(|d| d.amount))
+        .collect();
+    let shares = distribute_pro_rata(distributable, &stakes);
+    let undistributed = distributable - shares.iter().sum::<u64>();
+
+    RewardSplit {
+        validator: commission + shares[0] + undistributed,
+        commission,
+        delegators: delegations
+            .iter()
+            .zip(&shares[1..])
+            .map(|(d, share)| (d.delegator.clone(), *share))
+            .collect(),
+    }
+}
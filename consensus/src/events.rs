@@ -0,0 +1,196 @@
+// src/events.rs
+
+use crate::block::Hash;
+use crate::slashing::SlashReason;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Something that happened while a [`Network`](crate::Network) or a [`Node`](crate::node::Node)
+/// ran consensus.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ConsensusEvent {
+    /// A validator was drawn to propose in a round; an offline proposer is skipped.
+    ProposerSelected {
+        /// Height of the round.
+        height: u64,
+        /// Round at that height.
+        round: u32,
+        /// Identifier of the proposer.
+        proposer: String,
+        /// Whether the proposer was online to propose.
+        online: bool,
+    },
+    /// The proposer of a round proposed a block.
+    BlockProposed {
+        /// Height of the block.
+        height: u64,
+        /// Round the block was proposed in.
+        round: u32,
+        /// Identifier of the proposer.
+        proposer: String,
+        /// Hash of the block.
+        hash: Hash,
+        /// Transactions in the block.
+        transactions: usize,
+    },
+    /// A proposed block failed to gather a supermajority of precommits.
+    BlockRejected {
+        /// Height of the block.
+        height: u64,
+        /// Round the block was proposed in.
+        round: u32,
+        /// Identifier of the proposer.
+        proposer: String,
+        /// Hash of the block.
+        hash: Hash,
+    },
+    /// A proposed block was finalized and committed to the chain.
+    BlockFinalized {
+        /// Height of the block.
+        height: u64,
+        /// Round the block was proposed in.
+        round: u32,
+        /// Identifier of the proposer.
+        proposer: String,
+        /// Hash of the block.
+        hash: Hash,
+    },
+    /// The synergy score of a validator changed during a round.
+    ScoreUpdated {
+        /// Identifier of the validator.
+        validator: String,
+        /// Score before the round.
+        previous: f64,
+        /// Score after the round.
+        score: f64,
+    },
+    /// A validator was slashed for a violation.
+    ViolationRecorded {
+        /// Identifier of the validator.
+        validator: String,
+        /// Height of the chain head when the violation was punished.
+        height: u64,
+        /// The violation.
+        reason: SlashReason,
+        /// Stake taken from the validator and its delegators.
+        slashed: u64,
+    },
+}
+
+/// Receives the [`ConsensusEvent`]s of a network or node, for logging, metrics, indexing or tests.
+///
+/// Any `Fn(&ConsensusEvent)` closure is an observer.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::events::ConsensusEvent;
+/// use peo_consensus::{Network, PosygDcsEngine};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// let finalized = Arc::new(AtomicU64::new(0));
+/// let counter = Arc::clone(&finalized);
+/// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+/// let mut network = Network::from_seed(vec![validator], 1);
+/// network.subscribe(Arc::new(move |event: &ConsensusEvent| {
+///     if let ConsensusEvent::BlockFinalized { .. } = event {
+///         counter.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }));
+/// network.run_consensus_round().unwrap();
+/// network.run_consensus_round().unwrap();
+/// assert_eq!(finalized.load(Ordering::Relaxed), 2);
+/// ```
+pub trait Observer: Send + Sync {
+    /// Handles `event`.
+    fn on_event(&self, event: &ConsensusEvent);
+}
+
+impl<F: Fn(&ConsensusEvent) + Send + Sync> Observer for F {
+    fn on_event(&self, event: &ConsensusEvent) {
+        self(event)
+    }
+}
+
+/// The observers subscribed to a network or node, notified of every event in subscription order.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    observers: Vec<Arc<dyn Observer>>,
+}
+
+impl EventBus {
+    /// Creates a bus without observers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `observer`.
+    pub fn subscribe(&mut self, observer: Arc<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    /// Returns whether no observer is subscribed.
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Notifies every observer of `event`.
+    pub fn emit(&self, event: ConsensusEvent) {
+        for observer in &self.observers {
+            observer.on_event(&event);
+        }
+    }
+}
+
+/// An observer that records every event, so they can be inspected afterwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::events::{ConsensusEvent, EventLog};
+/// use peo_consensus::{Network, PosygDcsEngine};
+/// use std::sync::Arc;
+///
+/// let log = Arc::new(EventLog::new());
+/// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+/// let mut network = Network::from_seed(vec![validator], 1);
+/// network.subscribe(log.clone());
+/// network.run_consensus_round().unwrap();
+///
+/// let events = log.take();
+/// let first = &events[0];
+/// assert!(matches!(first, ConsensusEvent::ProposerSelected { proposer, .. } if proposer == "v1"));
+/// assert!(events.iter().any(|e| matches!(e, ConsensusEvent::BlockFinalized { height: 1, .. })));
+/// assert!(log.events().is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: Mutex<Vec<ConsensusEvent>>,
+}
+
+impl EventLog {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> Vec<ConsensusEvent> {
+        self.events.lock().expect("event log lock poisoned").clone()
+    }
+
+    /// Returns the events recorded so far and clears the log.
+    pub fn take(&self) -> Vec<ConsensusEvent> {
+        std::mem::take(&mut *self.events.lock().expect("event log lock poisoned"))
+    }
+}
+
+impl Observer for EventLog {
+    fn on_event(&self, event: &ConsensusEvent) {
+        self.events
+            .lock()
+            .expect("event log lock poisoned")
+            .push(event.clone());
+    }
+}
@@ -0,0 +1,316 @@
+// src/evidence.rs
+
+use crate::block::SignedHeader;
+use crate::crypto::ValidatorRegistry;
+use crate::vote::{Vote, VoteType};
+use crate::ConsensusError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Proof that a validator signed two conflicting statements at the same height and round: two
+/// votes of the same type for different blocks, or, as proposer, two different blocks.
+///
+/// Evidence is self-contained: anyone holding the validator's public key can check it without
+/// knowing anything else about the round.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::SigningKey;
+/// use peo_consensus::evidence::Evidence;
+/// use peo_consensus::{Hash, ValidatorRegistry, Vote, VoteType};
+/// use rand::rngs::OsRng;
+///
+/// let key = SigningKey::generate(&mut OsRng);
+/// let mut registry = ValidatorRegistry::new();
+/// registry.register("v1".to_string(), key.verifying_key());
+///
+/// let vote = |block_hash| {
+///     let mut vote = Vote {
+///         vote_type: VoteType::Prevote,
+///         height: 3,
+///         round: 0,
+///         block_hash,
+///         validator: "v1".to_string(),
+///         signature: None,
+///     };
+///     vote.sign(&key);
+///     vote
+/// };
+/// let evidence = Evidence::new(vote(Some(Hash::digest(b"a"))), vote(None)).unwrap();
+/// assert_eq!(evidence.validator(), "v1");
+/// assert!(evidence.verify(&registry).is_ok());
+/// assert!(Evidence::new(vote(None), vote(None)).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Evidence {
+    /// Two votes of the same type, height and round for different blocks.
+    DuplicateVote {
+        /// The vote seen first.
+        first: Vote,
+        /// The conflicting vote.
+        second: Vote,
+    },
+    /// Two different blocks signed by their proposer for the same height and round.
+    DoubleProposal {
+        /// The signed header seen first.
+        first: SignedHeader,
+        /// The conflicting signed header.
+        second: SignedHeader,
+    },
+}
+
+impl Evidence {
+    /// Pairs two votes as evidence, failing with `InvalidEvidence` unless they come from the same
+    /// validator, share type, height and round, and disagree on the block.
+    ///
+    /// Signatures are checked separately by [`Evidence::verify`].
+    pub fn new(first: Vote, second: Vote) -> Result<Self, ConsensusError> {
+        let evidence = Self::DuplicateVote { first, second };
+        evidence.check_conflict()?;
+        Ok(evidence)
+    }
+
+    /// Pairs two signed headers as evidence, failing with `InvalidEvidence` unless they come from
+    /// the same proposer, share height and round, and are for different blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::block::SignedHeader;
+    /// use peo_consensus::crypto::dev_signing_key;
+    /// use peo_consensus::evidence::Evidence;
+    /// use peo_consensus::Block;
+    ///
+    /// let key = dev_signing_key("v1");
+    /// let genesis = Block::genesis();
+    /// let a = Block::new(&genesis.header, "v1".to_string(), 1_000, vec![]);
+    /// let b = Block::new(&genesis.header, "v1".to_string(), 1_001, vec![]);
+    /// let sign = |block: &Block, round| SignedHeader::new(block.header.clone(), round, &key);
+    ///
+    /// let evidence = Evidence::double_proposal(sign(&a, 0), sign(&b, 0)).unwrap();
+    /// assert_eq!((evidence.validator(), evidence.height(), evidence.round()), ("v1", 1, 0));
+    /// assert_eq!(evidence.vote_type(), None);
+    /// // Re-proposing at the same height in a later round is allowed.
+    /// assert!(Evidence::double_proposal(sign(&a, 0), sign(&b, 1)).is_err());
+    /// ```
+    pub fn double_proposal(
+        first: SignedHeader,
+        second: SignedHeader,
+    ) -> Result<Self, ConsensusError> {
+        let evidence = Self::DoubleProposal { first, second };
+        evidence.check_conflict()?;
+        Ok(evidence)
+    }
+
+    /// Returns the equivocating validator.
+    pub fn validator(&self) -> &str {
+        match self {
+            Self::DuplicateVote { first, .. } => &first.validator,
+            Self::DoubleProposal { first, .. } => &first.header.proposer,
+        }
+    }
+
+    /// Returns the height at which the validator equivocated.
+    pub fn height(&self) -> u64 {
+        match self {
+            Self::DuplicateVote { first, .. } => first.height,
+            Self::DoubleProposal { first, .. } => first.header.height,
+        }
+    }
+
+    /// Returns the round in which the validator equivocated.
+    pub fn round(&self) -> u32 {
+        match self {
+            Self::DuplicateVote { first, .. } => first.round,
+            Self::DoubleProposal { first, .. } => first.round,
+        }
+    }
+
+    /// Returns the type of the conflicting votes, or `None` for conflicting proposals.
+    pub fn vote_type(&self) -> Option<VoteType> {
+        match self {
+            Self::DuplicateVote { first, .. } => Some(first.vote_type),
+            Self::DoubleProposal { .. } => None,
+        }
+    }
+
+    /// Returns the slot the validator equivocated in, which is punished at most once.
+    pub fn key(&self) -> EvidenceKey {
+        (
+            self.validator().to_string(),
+            self.vote_type(),
+            self.height(),
+            self.round(),
+        )
+    }
+
+    /// Checks that the two statements conflict and that both carry valid signatures of the
+    /// validator's registered key.
+    pub fn verify(&self, registry: &ValidatorRegistry) -> Result<(), ConsensusError> {
+        self.check_conflict()?;
+        match self {
+            Self::DuplicateVote { first, second } => {
+                first.verify_signature(registry)?;
+                second.verify_signature(registry)
+            }
+            Self::DoubleProposal { first, second } => {
+                first.verify(registry)?;
+                second.verify(registry)
+            }
+        }
+    }
+
+    fn check_conflict(&self) -> Result<(), ConsensusError> {
+        let conflicting = match self {
+            Self::DuplicateVote { first, second } => {
+                first.validator == second.validator
+                    && first.vote_type == second.vote_type
+                    && first.height == second.height
+                    && first.round == second.round
+                    && first.block_hash != second.block_hash
+            }
+            Self::DoubleProposal { first, second } => {
+                first.header.proposer == second.header.proposer
+                    && first.header.height == second.header.height
+                    && first.round == second.round
+                    && first.header.hash() != second.header.hash()
+            }
+        };
+        if !conflicting {
+            return Err(ConsensusError::InvalidEvidence);
+        }
+        Ok(())
+    }
+}
+
+/// Number of heights, counting back from the one being decided, that evidence is accepted for.
+///
+/// Older evidence is neither detected nor punished, so the record of punished violations stays
+/// bounded like the evidence pool.
+pub const EVIDENCE_MAX_AGE: u64 = 100;
+
+/// The slot a validator equivocates in: its identifier, the vote type (`None` for proposals), the
+/// height and the round.
+pub type EvidenceKey = (String, Option<VoteType>, u64, u32);
+
+/// Watches signed votes and proposals and turns conflicting pairs into [`Evidence`].
+///
+/// The pool remembers the first vote each validator cast per type, height and round, and the first
+/// header each proposer signed per height and round, and produces evidence the first time a
+/// conflicting one shows up; further conflicts in the same slot add nothing, since one piece of
+/// evidence is enough to punish it.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::evidence::EvidencePool;
+/// use peo_consensus::{Hash, Vote, VoteType};
+///
+/// let vote = |block_hash| Vote {
+///     vote_type: VoteType::Prevote,
+///     height: 1,
+///     round: 0,
+///     block_hash,
+///     validator: "v1".to_string(),
+///     signature: None,
+/// };
+/// let mut pool = EvidencePool::new();
+/// assert!(pool.observe(&vote(None)).is_none());
+/// assert!(pool.observe(&vote(None)).is_none());
+/// assert!(pool.observe(&vote(Some(Hash::digest(b"block")))).is_some());
+/// assert_eq!(pool.take_pending().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EvidencePool {
+    seen: HashMap<EvidenceKey, Vote>,
+    proposals: HashMap<EvidenceKey, SignedHeader>,
+    reported: HashSet<EvidenceKey>,
+    pending: Vec<Evidence>,
+}
+
+impl EvidencePool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `vote`, returning new evidence if it conflicts with a vote seen earlier.
+    pub fn observe(&mut self, vote: &Vote) -> Option<Evidence> {
+        let key = (
+            vote.validator.clone(),
+            Some(vote.vote_type),
+            vote.height,
+            vote.round,
+        );
+        let first = match self.seen.get(&key) {
+            Some(first) => first,
+            None => {
+                self.seen.insert(key, vote.clone());
+                return None;
+            }
+        };
+        if self.reported.contains(&key) {
+            return None;
+        }
+        let evidence = Evidence::new(first.clone(), vote.clone()).ok()?;
+        self.reported.insert(key);
+        self.pending.push(evidence.clone());
+        Some(evidence)
+    }
+
+    /// Records a signed proposal, returning new evidence if its proposer signed a different block
+    /// at the same height and round earlier.
+    pub fn observe_proposal(&mut self, header: &SignedHeader) -> Option<Evidence> {
+        let key = (
+            header.header.proposer.clone(),
+            None,
+            header.header.height,
+            header.round,
+        );
+        let first = match self.proposals.get(&key) {
+            Some(first) => first,
+            None => {
+                self.proposals.insert(key, header.clone());
+                return None;
+            }
+        };
+        if self.reported.contains(&key) {
+            return None;
+        }
+        let evidence = Evidence::double_proposal(first.clone(), header.clone()).ok()?;
+        self.reported.insert(key);
+        self.pending.push(evidence.clone());
+        Some(evidence)
+    }
+
+    /// Records evidence found elsewhere, returning whether it is new: evidence for a slot the pool
+    /// already has evidence for is dropped.
+    ///
+    /// The evidence is not verified here.
+    pub fn insert(&mut self, evidence: Evidence) -> bool {
+        if !self.reported.insert(evidence.key()) {
+            return false;
+        }
+        self.pending.push(evidence);
+        true
+    }
+
+    /// Returns evidence that has not been taken yet.
+    pub fn pending(&self) -> &[Evidence] {
+        &self.pending
+    }
+
+    /// Removes and returns all evidence that has not been taken yet.
+    pub fn take_pending(&mut self) -> Vec<Evidence> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Forgets every vote and proposal below `height`, bounding memory; conflicts at those heights
+    /// are no longer detected.
+    pub fn prune_below(&mut self, height: u64) {
+        self.seen.retain(|(_, _, h, _), _| *h >= height);
+        self.proposals.retain(|(_, _, h, _), _| *h >= height);
+        self.reported.retain(|(_, _, h, _)| *h >= height);
+    }
+}
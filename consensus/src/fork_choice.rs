@@ -0,0 +1,239 @@
+// src/fork_choice.rs
+
+use crate::block::{Block, Hash};
+use crate::ConsensusError;
+use std::collections::BTreeMap;
+
+/// A change of canonical head.
+///
+/// Blocks in `reverted` were on the old canonical chain and are not on the new one, newest first,
+/// so downstream state can be rolled back in order. Blocks in `applied` are on the new canonical
+/// chain and were not on the old one, oldest first, so they can be re-applied in order. A head that
+/// simply extends the old one reverts nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReorgEvent {
+    /// Blocks leaving the canonical chain, newest first.
+    pub reverted: Vec<Block>,
+    /// Blocks joining the canonical chain, oldest first.
+    pub applied: Vec<Block>,
+}
+
+impl ReorgEvent {
+    /// Returns `true` if the new head does not extend the old one.
+    pub fn is_reorg(&self) -> bool {
+        !self.reverted.is_empty()
+    }
+}
+
+struct Node {
+    block: Block,
+    weight: f64,
+}
+
+/// The tree of blocks built on top of the last finalized checkpoint.
+///
+/// Every block is inserted with a weight, such as the synergy-weighted selection weight of its
+/// proposer or the voting weight that endorsed it. The canonical head is the tip of the heaviest
+/// chain from the checkpoint, where the weight of a chain is the sum of its blocks' weights; a
+/// competing chain replaces the head only once it is strictly heavier. Finalizing a block moves the
+/// checkpoint to it and prunes every block that does not descend from it, so the canonical chain
+/// can never revert past a finalized block.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::fork_choice::BlockTree;
+/// use peo_consensus::Block;
+///
+/// let genesis = Block::genesis();
+/// let mut tree = BlockTree::new(genesis.clone());
+/// let a = Block::new(&genesis.header, "v1".to_string(), 1_000, vec![]);
+/// let b = Block::new(&genesis.header, "v2".to_string(), 1_000, vec![]);
+///
+/// let event = tree.insert(a.clone(), 1.0).unwrap().unwrap();
+/// assert!(!event.is_reorg());
+/// assert!(tree.insert(b.clone(), 1.0).unwrap().is_none());
+/// assert_eq!(tree.head().hash(), a.hash());
+///
+/// let b2 = Block::new(&b.header, "v2".to_string(), 2_000, vec![]);
+/// let event = tree.insert(b2.clone(), 1.0).unwrap().unwrap();
+/// assert_eq!(event.reverted, vec![a]);
+/// assert_eq!(event.applied, vec![b, b2.clone()]);
+/// assert_eq!(tree.head().hash(), b2.hash());
+/// ```
+pub struct BlockTree {
+    nodes: BTreeMap<Hash, Node>,
+    checkpoint: Hash,
+    head: Hash,
+}
+
+impl BlockTree {
+    /// Creates a tree anchored at the finalized block `checkpoint`.
+    pub fn new(checkpoint: Block) -> Self {
+        let hash = checkpoint.hash();
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            hash,
+            Node {
+                block: checkpoint,
+                weight: 0.0,
+            },
+        );
+        Self {
+            nodes,
+            checkpoint: hash,
+            head: hash,
+        }
+    }
+
+    /// Returns the canonical head.
+    pub fn head(&self) -> &Block {
+        &self.nodes[&self.head].block
+    }
+
+    /// Returns the last finalized block.
+    pub fn checkpoint(&self) -> &Block {
+        &self.nodes[&self.checkpoint].block
+    }
+
+    /// Returns the block with the given hash, if it is in the tree.
+    pub fn get(&self, hash: &Hash) -> Option<&Block> {
+        self.nodes.get(hash).map(|node| &node.block)
+    }
+
+    /// Returns `true` if the block with the given hash is in the tree.
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.nodes.contains_key(hash)
+    }
+
+    /// Returns the number of blocks in the tree, including the checkpoint.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree holds nothing but the checkpoint.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Returns the weight of the chain from the checkpoint to the block with the given hash.
+    pub fn weight(&self, hash: &Hash) -> Option<f64> {
+        self.nodes.get(hash).map(|node| node.weight)
+    }
+
+    /// Returns the canonical chain from the checkpoint to the head, oldest first.
+    pub fn canonical_chain(&self) -> Vec<&Block> {
+        let mut chain: Vec<&Block> = self
+            .ancestry(self.head)
+            .map(|hash| &self.nodes[&hash].block)
+            .collect();
+        chain.reverse();
+        chain
+    }
+
+    /// Inserts `block` with the given `weight`, returning the head change it causes, if any.
+    ///
+    /// Inserting a block that is already in the tree changes nothing. A block whose parent is not
+    /// in the tree, or whose height does not follow its parent's, is rejected.
+    pub fn insert(
+        &mut self,
+        block: Block,
+        weight: f64,
+    ) -> Result<Option<ReorgEvent>, ConsensusError> {
+        let hash = block.hash();
+        if self.nodes.contains_key(&hash) {
+            return Ok(None);
+        }
+        let parent = self
+            .nodes
+            .get(&block.header.parent_hash)
+            .ok_or(ConsensusError::InvalidParent)?;
+        if block.header.height != parent.block.header.height + 1 {
+            return Err(ConsensusError::InvalidHeight);
+        }
+
+        let weight = parent.weight + weight.max(0.0);
+        self.nodes.insert(hash, Node { block, weight });
+        if weight > self.nodes[&self.head].weight {
+            Ok(Some(self.set_head(hash)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Moves the checkpoint to the block with the given hash and prunes every block that does not
+    /// descend from it, returning the head change this causes, if any.
+    ///
+    /// If the old head does not descend from the new checkpoint, the heaviest chain that does
+    /// becomes canonical, ties going to the lowest block hash.
+    pub fn finalize(&mut self, hash: Hash) -> Result<Option<ReorgEvent>, ConsensusError> {
+        if !self.nodes.contains_key(&hash) {
+            return Err(ConsensusError::InvalidBlock);
+        }
+        if hash == self.checkpoint {
+            return Ok(None);
+        }
+
+        let descendants: Vec<Hash> = self
+            .nodes
+            .keys()
+            .copied()
+            .filter(|&h| self.ancestry(h).any(|a| a == hash))
+            .collect();
+        let event = if descendants.contains(&self.head) {
+            None
+        } else {
+            let best = descendants
+                .iter()
+                .copied()
+                .reduce(|best, h| {
+                    if self.nodes[&h].weight > self.nodes[&best].weight {
+                        h
+                    } else {
+                        best
+                    }
+                })
+                .unwrap_or(hash);
+            Some(self.set_head(best))
+        };
+
+        self.nodes.retain(|h, _| descendants.contains(h));
+        self.checkpoint = hash;
+        Ok(event)
+    }
+
+    fn set_head(&mut self, new_head: Hash) -> ReorgEvent {
+        let old: Vec<Hash> = self.ancestry(self.head).collect();
+        let new: Vec<Hash> = self.ancestry(new_head).collect();
+        let reverted = old
+            .iter()
+            .take_while(|h| !new.contains(h))
+            .map(|h| self.nodes[h].block.clone())
+            .collect();
+        let mut applied: Vec<Block> = new
+            .iter()
+            .take_while(|h| !old.contains(h))
+            .map(|h| self.nodes[h].block.clone())
+            .collect();
+        applied.reverse();
+        self.head = new_head;
+        ReorgEvent { reverted, applied }
+    }
+
+    /// Walks from the block with the given hash back to the checkpoint, both included.
+    fn ancestry(&self, from: Hash) -> impl Iterator<Item = Hash> + '_ {
+        let checkpoint = self.checkpoint;
+        let mut next = Some(from);
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = if current == checkpoint {
+                None
+            } else {
+                self.nodes
+                    .get(&current)
+                    .map(|node| node.block.header.parent_hash)
+            };
+            Some(current)
+        })
+    }
+}
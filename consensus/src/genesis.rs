@@ -0,0 +1,243 @@
+// src/genesis.rs
+
+use crate::crypto::{decode_key, dev_signing_key, SigningKey, ValidatorRegistry, VerifyingKey};
+use crate::node::{Node, ValidatorInfo};
+use crate::scoring::{DcsParams, DcsPolicy};
+use crate::staking::DEFAULT_EPOCH_LENGTH;
+use crate::{ConsensusError, Network, PosygDcsEngine};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A validator of the genesis validator set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisValidator {
+    /// Identifier of the validator.
+    pub id: String,
+    /// Initial stake of the validator.
+    pub stake: u64,
+    /// Hex-encoded Ed25519 public key of the validator. Without one, the validator can only be
+    /// simulated, with the development key derived from its identifier; network nodes refuse a
+    /// genesis that lacks any validator's key.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Whether the validator proposes invalid blocks, for simulations.
+    #[serde(default)]
+    pub malicious: bool,
+}
+
+impl GenesisValidator {
+    /// Returns the public key of the validator.
+    pub fn verifying_key(&self) -> Result<VerifyingKey, ConsensusError> {
+        let Some(hex) = &self.key else {
+            return Ok(dev_signing_key(&self.id).verifying_key());
+        };
+        let invalid = || {
+            ConsensusError::InvalidConfig(format!("validator {}: invalid key '{}'", self.id, hex))
+        };
+        let bytes = decode_key(hex).ok_or_else(invalid)?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())
+    }
+}
+
+/// The initial state of a chain: its identifier, validator set, scoring parameters and epoch
+/// length.
+///
+/// The same file drives a local simulation of the whole validator set and every node of a
+/// multi-process network, so all of them start from the same state.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::dev_signing_key;
+/// use peo_consensus::genesis::Genesis;
+///
+/// let genesis = Genesis::from_toml_str(
+///     r#"
+///     chain_id = "peochain-devnet"
+///     epoch_length = 50
+///
+///     [scoring]
+///     alpha = 1.0
+///
+///     [[validators]]
+///     id = "v1"
+///     stake = 1000
+///
+///     [[validators]]
+///     id = "v2"
+///     stake = 800
+///     malicious = true
+///     "#,
+/// )
+/// .unwrap();
+/// assert_eq!(genesis.validators.len(), 2);
+///
+/// let mut network = genesis.network(Some(7)).unwrap();
+/// assert_eq!(network.epoch_length(), 50);
+/// network.run_consensus_round().unwrap();
+///
+/// // Without the validators' public keys the genesis only drives simulations.
+/// assert!(genesis.node("v1", dev_signing_key("v1")).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Genesis {
+    /// Identifier of the chain.
+    pub chain_id: String,
+    /// Blocks per epoch.
+    #[serde(default = "default_epoch_length")]
+    pub epoch_length: u64,
+    /// Parameters of the scoring formula; missing ones take their default values.
+    #[serde(default)]
+    pub scoring: DcsParams,
+    /// The initial validators.
+    pub validators: Vec<GenesisValidator>,
+}
+
+fn default_epoch_length() -> u64 {
+    DEFAULT_EPOCH_LENGTH
+}
+
+impl Genesis {
+    /// Parses and validates a genesis from a TOML document.
+    pub fn from_toml_str(s: &str) -> Result<Self, ConsensusError> {
+        let genesis: Self =
+            toml::from_str(s).map_err(|e| ConsensusError::InvalidConfig(e.to_string()))?;
+        genesis.validate()?;
+        Ok(genesis)
+    }
+
+    /// Parses and validates a genesis from a JSON document.
+    pub fn from_json_str(s: &str) -> Result<Self, ConsensusError> {
+        let genesis: Self =
+            serde_json::from_str(s).map_err(|e| ConsensusError::InvalidConfig(e.to_string()))?;
+        genesis.validate()?;
+        Ok(genesis)
+    }
+
+    /// Loads and validates a genesis from a `.toml` or `.json` file, chosen by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConsensusError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConsensusError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(ConsensusError::InvalidConfig(format!(
+                "{}: expected a .toml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Checks that the chain has an identifier, an epoch length and a non-empty set of
+    /// validators with distinct identifiers, positive stakes and well-formed keys.
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        if self.chain_id.is_empty() {
+            return Err(ConsensusError::InvalidConfig(
+                "chain_id must not be empty".to_string(),
+            ));
+        }
+        if self.epoch_length == 0 {
+            return Err(ConsensusError::InvalidConfig(
+                "epoch_length must be positive".to_string(),
+            ));
+        }
+        if self.validators.is_empty() {
+            return Err(ConsensusError::EmptyValidatorSet);
+        }
+        let mut ids = HashSet::new();
+        for validator in &self.validators {
+            if !ids.insert(validator.id.as_str()) {
+                return Err(ConsensusError::DuplicateValidator);
+            }
+            if validator.stake == 0 {
+                return Err(ConsensusError::InvalidStake);
+            }
+            validator.verifying_key()?;
+        }
+        Ok(())
+    }
+
+    /// Returns validator `id`, if it is in the genesis set.
+    pub fn validator(&self, id: &str) -> Option<&GenesisValidator> {
+        self.validators.iter().find(|v| v.id == id)
+    }
+
+    /// Returns the validator set a [`Node`] runs consensus with.
+    pub fn validator_set(&self) -> Result<Vec<ValidatorInfo>, ConsensusError> {
+        self.validators
+            .iter()
+            .map(|v| {
+                Ok(ValidatorInfo {
+                    id: v.id.clone(),
+                    stake: v.stake,
+                    key: v.verifying_key()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a registry of the genesis keys, which peers prove their identity against.
+    pub fn registry(&self) -> Result<ValidatorRegistry, ConsensusError> {
+        let mut registry = ValidatorRegistry::new();
+        for v in &self.validators {
+            registry.register(v.id.clone(), v.verifying_key()?);
+        }
+        Ok(registry)
+    }
+
+    /// Creates a network simulating the whole validator set in one process, seeded with `seed` or
+    /// from operating system entropy.
+    ///
+    /// A simulation holds every validator's signing key, so validators sign with the development
+    /// keys derived from their identifiers; the keys in the genesis only matter to [`Node`]s.
+    pub fn network(&self, seed: Option<u64>) -> Result<Network, ConsensusError> {
+        self.validate()?;
+        let validators = self
+            .validators
+            .iter()
+            .map(|v| PosygDcsEngine::with_dev_key(v.id.clone(), v.stake, v.malicious))
+            .collect();
+        let mut network = match seed {
+            Some(seed) => Network::from_seed(validators, seed),
+            None => Network::new(validators),
+        };
+        network.set_scoring_policy(Arc::new(DcsPolicy::new(self.scoring)));
+        network.set_epoch_length(self.epoch_length);
+        Ok(network)
+    }
+
+    /// Creates the node of validator `id`, signing with `signing_key`.
+    ///
+    /// Fails if `id` is not in the genesis set, if the signing key does not match its public key,
+    /// or if any validator lacks a public key: a development key could be derived by anyone, so a
+    /// node would accept messages from impostors.
+    pub fn node(&self, id: &str, signing_key: SigningKey) -> Result<Node, ConsensusError> {
+        self.validate()?;
+        let validator = self.validator(id).ok_or(ConsensusError::UnknownValidator)?;
+        if let Some(keyless) = self.validators.iter().find(|v| v.key.is_none()) {
+            return Err(ConsensusError::InvalidConfig(format!(
+                "validator {} has no key; a network node needs the key of every validator",
+                keyless.id
+            )));
+        }
+        if signing_key.verifying_key() != validator.verifying_key()? {
+            return Err(ConsensusError::InvalidConfig(format!(
+                "the signing key of {} does not match its genesis key",
+                id
+            )));
+        }
+        let mut engine = PosygDcsEngine::with_signing_key(
+            id.to_string(),
+            validator.stake,
+            validator.malicious,
+            signing_key,
+        );
+        engine.set_scoring_policy(Arc::new(DcsPolicy::new(self.scoring)));
+        Node::new(engine, self.validator_set()?)
+    }
+}
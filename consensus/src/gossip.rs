@@ -0,0 +1,215 @@
+// src/gossip.rs
+
+use crate::block::Hash;
+use crate::node::Message;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+/// Identifies a message across the network: the digest of its encoding.
+pub fn message_id(message: &Message) -> Hash {
+    Hash::digest(&serde_json::to_vec(message).expect("messages always serialize"))
+}
+
+/// Tuning of a [`Gossip`] router.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GossipConfig {
+    /// Number of peers each new message is sent or forwarded to.
+    pub fanout: usize,
+    /// How long, in milliseconds, a message ID is at least remembered; a copy arriving once it is
+    /// forgotten is treated as new.
+    pub seen_ttl_ms: u64,
+    /// Most messages accepted from a single peer per rate window.
+    pub rate_limit: u32,
+    /// Length of a rate window, in milliseconds.
+    pub rate_window_ms: u64,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            fanout: 6,
+            seen_ttl_ms: 120_000,
+            rate_limit: 1_000,
+            rate_window_ms: 1_000,
+        }
+    }
+}
+
+/// What a [`Gossip`] router decided about a received message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Received {
+    /// The message is new: deliver it locally and forward it to these peers.
+    Fresh(Vec<String>),
+    /// The message was seen before and is dropped.
+    Duplicate,
+    /// The message was seen before but is sent again by the validator that signed it, which
+    /// resends its votes when a round stalls: deliver it locally without forwarding it.
+    Resent,
+    /// The sending peer exceeded its rate limit and the message is dropped.
+    RateLimited,
+}
+
+/// Counters of a [`Gossip`] router's activity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GossipStats {
+    /// Messages published by this node.
+    pub published: u64,
+    /// New messages received from peers.
+    pub delivered: u64,
+    /// Copies of already seen messages that were dropped.
+    pub duplicates: u64,
+    /// Messages dropped because their sender exceeded its rate limit.
+    pub rate_limited: u64,
+    /// Copies sent to peers, for published and forwarded messages alike.
+    pub sent: u64,
+}
+
+/// Decides which peers each message is relayed to, so messages reach the whole network without
+/// every node sending everything to everyone.
+///
+/// A published message goes to `fanout` random peers, and every node that receives a message for
+/// the first time forwards it to `fanout` random peers other than the sender. Message IDs are
+/// remembered for at least `seen_ttl_ms` so copies arriving over other paths are dropped, and each
+/// peer may send at most `rate_limit` messages per window. A node that publishes a message again,
+/// as it does with its votes when a round times out, sends it to every peer, and peers deliver a
+/// vote they have already seen again when it comes from its signer, so resent votes are not lost
+/// to deduplication. The router only makes decisions; the
+/// caller moves the messages over its transport. Time is passed in as milliseconds on any clock.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::block::Hash;
+/// use peo_consensus::gossip::{Gossip, GossipConfig, Received};
+/// use peo_consensus::node::Message;
+///
+/// let config = GossipConfig { fanout: 2, ..GossipConfig::default() };
+/// let mut gossip = Gossip::new(config, 7);
+/// let peers: Vec<String> = ["a", "b", "c"].iter().map(|p| p.to_string()).collect();
+/// let message = Message::Hello {
+///     version: 1,
+///     chain_id: "peochain".to_string(),
+///     validator_id: "v1".to_string(),
+///     nonce: Hash::ZERO,
+/// };
+///
+/// match gossip.receive("a", &message, &peers, 0) {
+///     Received::Fresh(targets) => {
+///         assert_eq!(targets.len(), 2);
+///         assert!(!targets.contains(&"a".to_string()));
+///     }
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// assert_eq!(gossip.receive("b", &message, &peers, 10), Received::Duplicate);
+/// assert!(matches!(gossip.receive("b", &message, &peers, 200_000), Received::Fresh(_)));
+/// ```
+pub struct Gossip {
+    config: GossipConfig,
+    rng: StdRng,
+    seen: HashMap<Hash, u64>,
+    rates: HashMap<String, (u64, u32)>,
+    last_expiry: u64,
+    stats: GossipStats,
+}
+
+impl Gossip {
+    /// Creates a router whose random peer choices are drawn from `seed`.
+    pub fn new(config: GossipConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            seen: HashMap::new(),
+            rates: HashMap::new(),
+            last_expiry: 0,
+            stats: GossipStats::default(),
+        }
+    }
+
+    /// Returns the router's configuration.
+    pub fn config(&self) -> &GossipConfig {
+        &self.config
+    }
+
+    /// Returns the router's activity counters.
+    pub fn stats(&self) -> GossipStats {
+        self.stats
+    }
+
+    /// Returns the number of message IDs currently remembered.
+    pub fn seen_len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Records a message created by this node, returning the peers to send it to: `fanout` of
+    /// them, or every peer if the message was published before.
+    pub fn publish(&mut self, message: &Message, peers: &[String], now: u64) -> Vec<String> {
+        self.expire(now);
+        let resent = self.seen.insert(message_id(message), now).is_some();
+        self.stats.published += 1;
+        if resent {
+            self.stats.sent += peers.len() as u64;
+            return peers.to_vec();
+        }
+        self.pick(peers, None)
+    }
+
+    /// Decides what to do with `message` received from `from`.
+    pub fn receive(
+        &mut self,
+        from: &str,
+        message: &Message,
+        peers: &[String],
+        now: u64,
+    ) -> Received {
+        self.expire(now);
+        let (window_start, count) = self.rates.entry(from.to_string()).or_insert((now, 0));
+        if now.saturating_sub(*window_start) >= self.config.rate_window_ms {
+            *window_start = now;
+            *count = 0;
+        }
+        if *count >= self.config.rate_limit {
+            self.stats.rate_limited += 1;
+            return Received::RateLimited;
+        }
+        *count += 1;
+
+        let id = message_id(message);
+        if self.seen.contains_key(&id) {
+            if matches!(message, Message::Vote(vote) if vote.validator == from) {
+                self.stats.delivered += 1;
+                return Received::Resent;
+            }
+            self.stats.duplicates += 1;
+            return Received::Duplicate;
+        }
+        self.seen.insert(id, now);
+        self.stats.delivered += 1;
+        Received::Fresh(self.pick(peers, Some(from)))
+    }
+
+    /// Forgets message IDs older than the seen TTL; runs at most once per TTL.
+    fn expire(&mut self, now: u64) {
+        if now.saturating_sub(self.last_expiry) < self.config.seen_ttl_ms {
+            return;
+        }
+        let ttl = self.config.seen_ttl_ms;
+        self.seen
+            .retain(|_, seen_at| now.saturating_sub(*seen_at) < ttl);
+        self.last_expiry = now;
+    }
+
+    fn pick(&mut self, peers: &[String], exclude: Option<&str>) -> Vec<String> {
+        let candidates: Vec<&String> = peers
+            .iter()
+            .filter(|p| Some(p.as_str()) != exclude)
+            .collect();
+        let targets: Vec<String> = candidates
+            .choose_multiple(&mut self.rng, self.config.fanout)
+            .map(|p| p.to_string())
+            .collect();
+        self.stats.sent += targets.len() as u64;
+        targets
+    }
+}
@@ -1,7 +1,61 @@
 // src/lib.rs
 
-use rand::Rng;
-use rand::thread_rng;
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, RngCore, SeedableRng};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use crypto::{dev_signing_key, SigningKey, VerifyingKey};
+use delegation::{DelegationBook, RewardSplit};
+use events::{ConsensusEvent, EventBus, Observer};
+use evidence::{Evidence, EvidenceKey, EvidencePool, EVIDENCE_MAX_AGE};
+use fork_choice::{BlockTree, ReorgEvent};
+use mempool::{Mempool, MempoolConfig};
+use rewards::{IssuanceSchedule, Ledger};
+use scoring::{DcsPolicy, ScoreInputs, ScoringPolicy};
+use slashing::{SlashDestination, SlashReason, SlashRecord, SlashingParams};
+use staking::{
+    EpochSnapshot, ReleasedStake, UnbondingDelegation, UnbondingValidator, DEFAULT_EPOCH_LENGTH,
+    DEFAULT_UNBONDING_PERIOD,
+};
+use state::AccountState;
+use timeout::TimeoutConfig;
+use transaction::{Transaction, TransactionKind};
+
+pub mod analysis;
+pub mod block;
+pub mod crypto;
+pub mod delegation;
+pub mod events;
+pub mod evidence;
+pub mod fork_choice;
+pub mod genesis;
+pub mod gossip;
+pub mod mempool;
+pub mod metrics;
+pub mod net;
+pub mod node;
+pub mod outcome;
+pub mod rewards;
+pub mod rpc;
+pub mod scheduler;
+pub mod scoring;
+pub mod sim;
+pub mod slashing;
+pub mod staking;
+pub mod state;
+pub mod timeout;
+pub mod transaction;
+pub mod vote;
+
+pub use block::{Block, BlockHeader, Hash, SignedHeader};
+pub use crypto::ValidatorRegistry;
+pub use outcome::{RoundOutcome, Verdict};
+pub use vote::{QuorumCertificate, Vote, VoteSet, VoteType, VoteWeighting};
+
+/// Logical time, in milliseconds, between the timestamps of consecutive blocks proposed by a
+/// simulated [`Network`].
+pub const BLOCK_INTERVAL_MS: u64 = 1_000;
 
 /// Represents errors that can occur during consensus operations.
 ///
@@ -22,33 +76,78 @@ pub enum ConsensusError {
     InvalidBlock,
     /// Represents a network-related failure, with a descriptive message.
     NetworkError(String),
+    /// The block's height is not one above the current tip.
+    InvalidHeight,
+    /// The block's parent hash does not match the hash of the current tip.
+    InvalidParent,
+    /// The block's timestamp is not later than the current tip's timestamp.
+    InvalidTimestamp,
+    /// The header's transactions root does not commit to the block's transactions.
+    InvalidTransactionsRoot,
+    /// The header's state root does not match the state derived from the parent.
+    InvalidStateRoot,
+    /// A validator with the same identifier is already active, pending or unbonding.
+    DuplicateValidator,
+    /// A stake amount is zero or otherwise unacceptable.
+    InvalidStake,
+    /// The change would leave the network without any active validator.
+    EmptyValidatorSet,
+    /// A proposal or vote is unsigned or its signature does not match the signer's registered key.
+    InvalidSignature,
+    /// A proposal or vote claims to come from a validator with no registered key.
+    UnknownValidator,
+    /// Submitted evidence does not prove a violation.
+    InvalidEvidence,
+    /// The violation proven by submitted evidence has already been punished.
+    DuplicateEvidence,
+    /// A submitted transaction is empty, too large or otherwise malformed.
+    InvalidTransaction,
+    /// A submitted transaction is already pending in the mempool.
+    DuplicateTransaction,
+    /// The mempool is full of transactions paying at least as much as a submitted one.
+    MempoolFull,
+    /// A transaction's sender has no account, or no key to sign with.
+    UnknownAccount,
+    /// A transaction's nonce is not the next one of its sender.
+    InvalidNonce,
+    /// A transaction's sender cannot pay its fee and amount.
+    InsufficientFunds,
+    /// A configuration file or document could not be read or parsed.
+    InvalidConfig(String),
+    /// Submitted evidence concerns a height too far behind the chain head to be punished.
+    ExpiredEvidence,
+    /// State that consensus guarantees to be consistent is not, such as a certified block that
+    /// validators cannot commit; the message says which guarantee broke.
+    InvariantViolated(String),
 }
 
 /// Defines the behavior required for a consensus engine in the PeoChain network.
 ///
-/// Implementors of this trait must provide methods to propose blocks, validate them, update scores
-/// based on consensus outcomes, and retrieve their synergy score.
+/// Implementors of this trait must provide methods to propose blocks on top of their current tip,
+/// validate and commit them, update scores based on consensus outcomes, and retrieve their synergy
+/// score.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use peo_consensus::{ConsensusEngine, ConsensusError, Block};
+/// use peo_consensus::{Block, BlockHeader, ConsensusEngine, ConsensusError};
 ///
 /// struct DummyEngine {
+///     tip: BlockHeader,
 ///     score: f64,
 /// }
 ///
 /// impl ConsensusEngine for DummyEngine {
-///     fn propose_block(&self) -> Result<Block, ConsensusError> {
-///         Ok(Block {
-///             id: 1,
-///             proposer: "dummy".to_string(),
-///             transactions: vec![],
-///         })
+///     fn propose_block(&self, timestamp: u64) -> Result<Block, ConsensusError> {
+///         Ok(Block::new(&self.tip, "dummy".to_string(), timestamp, vec![]))
 ///     }
 ///     fn validate_block(&self, _block: &Block) -> Result<(), ConsensusError> {
 ///         Ok(())
 ///     }
+///     fn commit_block(&mut self, block: &Block) -> Result<(), ConsensusError> {
+///         self.tip = block.header.clone();
+///         Ok(())
+///     }
 ///     fn update_scores(&mut self, block_accepted: bool, _violation: bool) {
 ///         if block_accepted {
 ///             self.score += 1.0;
@@ -59,18 +158,23 @@ pub enum ConsensusError {
 ///     }
 /// }
 ///
-/// let mut engine = DummyEngine { score: 0.0 };
-/// let block = engine.propose_block().unwrap();
+/// let mut engine = DummyEngine { tip: BlockHeader::genesis(), score: 0.0 };
+/// let block = engine.propose_block(1_000).unwrap();
+/// engine.commit_block(&block).unwrap();
 /// engine.update_scores(true, false);
 /// assert_eq!(engine.get_synergy_score(), 1.0);
 /// ```
 pub trait ConsensusEngine {
-    /// Proposes a new block for inclusion in the blockchain.
-    fn propose_block(&self) -> Result<Block, ConsensusError>;
+    /// Proposes a new block extending the engine's current tip, stamped with `timestamp`.
+    fn propose_block(&self, timestamp: u64) -> Result<Block, ConsensusError>;
 
-    /// Validates a proposed block according to consensus rules.
+    /// Validates a proposed block according to consensus rules, including its linkage to the
+    /// current tip.
     fn validate_block(&self, block: &Block) -> Result<(), ConsensusError>;
 
+    /// Makes an accepted block the engine's new tip.
+    fn commit_block(&mut self, block: &Block) -> Result<(), ConsensusError>;
+
     /// Updates the validator's synergy score based on the outcome of a block proposal.
     fn update_scores(&mut self, block_accepted: bool, violation_occurred: bool);
 
@@ -78,38 +182,54 @@ pub trait ConsensusEngine {
     fn get_synergy_score(&self) -> f64;
 }
 
-/// Represents a block in the PeoChain blockchain.
-///
-/// A block contains an identifier, the proposer's ID, and a list of transactions.
+/// A validator's record of validating blocks proposed by others.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use peo_consensus::Block;
+/// use peo_consensus::ValidationStats;
 ///
-/// let block = Block {
-///     id: 1,
-///     proposer: "validator1".to_string(),
-///     transactions: vec!["tx1".to_string()],
-/// };
-/// assert_eq!(block.id, 1);
-/// assert_eq!(block.proposer, "validator1");
-/// assert_eq!(block.transactions, vec!["tx1"]);
+/// let stats = ValidationStats { blocks_validated: 4, correct_verdicts: 3, timely_votes: 4 };
+/// assert_eq!(stats.accuracy(), 0.75);
 /// ```
-#[derive(Clone)]
-pub struct Block {
-    /// Unique identifier for the block.
-    pub id: u64,
-    /// Identifier of the validator that proposed the block.
-    pub proposer: String,
-    /// List of transactions included in the block.
-    pub transactions: Vec<String>,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidationStats {
+    /// Number of other validators' proposals this validator voted on.
+    pub blocks_validated: u64,
+    /// Number of those votes whose verdict matched the round's outcome.
+    pub correct_verdicts: u64,
+    /// Number of those votes that were cast before the round's deadline.
+    pub timely_votes: u64,
+}
+
+impl ValidationStats {
+    /// Returns the share of verdicts that matched their round's outcome, 0 if none was cast.
+    pub fn accuracy(&self) -> f64 {
+        if self.blocks_validated == 0 {
+            0.0
+        } else {
+            self.correct_verdicts as f64 / self.blocks_validated as f64
+        }
+    }
+}
+
+/// How a malicious validator misbehaves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MaliciousStrategy {
+    /// Proposes blocks containing an invalid transaction.
+    #[default]
+    InvalidBlock,
+    /// Proposes and prevotes valid blocks, but also signs a conflicting proposal and prevote in
+    /// every round.
+    Equivocate,
 }
 
 /// Implements the PoSyg + DCS consensus engine for a single validator.
 ///
 /// This struct encapsulates the state and behavior of a validator in the PeoChain network,
-/// including its synergy score, stake, and proposal history.
+/// including its synergy score, stake, proposal history and the tip of its chain. Each validator
+/// holds an ed25519 signing key for its proposals and votes, and a registry of the public keys of
+/// the validators whose blocks it accepts.
 ///
 /// # Examples
 ///
@@ -117,7 +237,11 @@ pub struct Block {
 /// use peo_consensus::{PosygDcsEngine, ConsensusEngine, ConsensusError, Block};
 ///
 /// let mut validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
-/// let block = validator.propose_block().unwrap();
+/// let block = validator.propose_block(1_000).unwrap();
+/// assert_eq!(block.header.height, 1);
+/// assert!(block.verify_signature(validator.registry()).is_ok());
+/// validator.commit_block(&block).unwrap();
+/// assert_eq!(validator.tip().hash(), block.hash());
 /// validator.update_scores(true, false);
 /// assert_eq!(validator.get_synergy_score(), 3.4);
 /// assert_eq!(validator.proposed_blocks(), 0); // Not incremented yet
@@ -132,17 +256,67 @@ pub struct PosygDcsEngine {
     accepted_blocks: u64,
     violations: u64,
     is_malicious: bool,
+    strategy: MaliciousStrategy,
+    tip: BlockHeader,
+    signing_key: SigningKey,
+    registry: ValidatorRegistry,
+    validation_stats: ValidationStats,
+    pending_validation: f64,
+    scoring: Arc<dyn ScoringPolicy>,
+    slash_history: Vec<SlashRecord>,
+    delegated_stake: u64,
+    commission_rate: f64,
+    accounts: AccountState,
+    online: bool,
+    downtime: u64,
+    vote_delay_ms: u64,
 }
 
 impl PosygDcsEngine {
     /// Creates a new validator with the specified ID, stake, and malicious behavior flag.
     ///
+    /// A fresh signing key is generated from operating system entropy. Use
+    /// [`with_signing_key`](Self::with_signing_key) to sign with a key the validator already
+    /// holds.
+    ///
     /// # Arguments
     ///
     /// * `validator_id` - A unique identifier for the validator.
     /// * `stake` - The amount of stake the validator has committed.
-    /// * `is_malicious` - If true, the validator will propose invalid blocks.
+    /// * `is_malicious` - If true, the validator misbehaves according to its [`MaliciousStrategy`],
+    ///   by default proposing invalid blocks.
     pub fn new(validator_id: String, stake: u64, is_malicious: bool) -> Self {
+        Self::with_signing_key(
+            validator_id,
+            stake,
+            is_malicious,
+            SigningKey::generate(&mut OsRng),
+        )
+    }
+
+    /// Creates a new validator that signs with the development key derived from its identifier.
+    ///
+    /// Anyone can derive that key (see [`crypto::dev_signing_key`]), so this is only meant for
+    /// simulations and tests, where every validator must sign the same way from run to run.
+    pub fn with_dev_key(validator_id: String, stake: u64, is_malicious: bool) -> Self {
+        let signing_key = dev_signing_key(&validator_id);
+        Self::with_signing_key(validator_id, stake, is_malicious, signing_key)
+    }
+
+    /// Creates a new validator that signs with the given key.
+    ///
+    /// The validator's own public key is registered, so it accepts its own proposals, and it
+    /// accepts stake delegated to itself.
+    pub fn with_signing_key(
+        validator_id: String,
+        stake: u64,
+        is_malicious: bool,
+        signing_key: SigningKey,
+    ) -> Self {
+        let mut registry = ValidatorRegistry::new();
+        registry.register(validator_id.clone(), signing_key.verifying_key());
+        let mut accounts = AccountState::new();
+        accounts.add_validator(validator_id.clone());
         Self {
             validator_id,
             synergy_score: 0.0,
@@ -151,9 +325,33 @@ impl PosygDcsEngine {
             accepted_blocks: 0,
             violations: 0,
             is_malicious,
+            strategy: MaliciousStrategy::default(),
+            tip: BlockHeader::genesis(),
+            signing_key,
+            registry,
+            validation_stats: ValidationStats::default(),
+            pending_validation: 0.0,
+            scoring: Arc::new(DcsPolicy::default()),
+            slash_history: Vec::new(),
+            delegated_stake: 0,
+            commission_rate: 0.0,
+            accounts,
+            online: true,
+            downtime: 0,
+            vote_delay_ms: 0,
         }
     }
 
+    /// Sets the formula used to update the validator's synergy score.
+    pub fn set_scoring_policy(&mut self, policy: Arc<dyn ScoringPolicy>) {
+        self.scoring = policy;
+    }
+
+    /// Returns the formula used to update the validator's synergy score.
+    pub fn scoring_policy(&self) -> &Arc<dyn ScoringPolicy> {
+        &self.scoring
+    }
+
     /// Returns the validator's unique identifier.
     pub fn validator_id(&self) -> &str {
         &self.validator_id
@@ -179,11 +377,356 @@ impl PosygDcsEngine {
         self.stake
     }
 
+    /// Removes `amount` from the validator's stake as punishment for `reason` at `height`, and
+    /// records it in the slash history.
+    ///
+    /// The amount is capped at the current stake; the amount actually removed is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::PosygDcsEngine;
+    /// use peo_consensus::slashing::SlashReason;
+    ///
+    /// let mut validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// assert_eq!(validator.slash(SlashReason::InvalidBlock, 10, 3), 10);
+    /// assert_eq!(validator.stake(), 990);
+    /// assert_eq!(validator.slash_history()[0].height, 3);
+    /// ```
+    pub fn slash(&mut self, reason: SlashReason, amount: u64, height: u64) -> u64 {
+        let amount = amount.min(self.stake);
+        self.stake -= amount;
+        self.slash_history.push(SlashRecord {
+            height,
+            reason,
+            amount,
+            delegated_amount: 0,
+        });
+        amount
+    }
+
+    /// Returns every slashing applied to the validator, oldest first.
+    pub fn slash_history(&self) -> &[SlashRecord] {
+        &self.slash_history
+    }
+
+    /// Adds `amount` to the validator's stake.
+    pub fn add_stake(&mut self, amount: u64) {
+        self.stake += amount;
+    }
+
+    /// Returns the stake delegators have delegated to the validator.
+    pub fn delegated_stake(&self) -> u64 {
+        self.delegated_stake
+    }
+
+    /// Returns the validator's own stake plus the stake delegated to it, which together determine
+    /// its weight in proposer selection and voting.
+    pub fn total_stake(&self) -> u64 {
+        self.stake + self.delegated_stake
+    }
+
+    /// Returns the fraction of delegators' rewards the validator keeps as commission.
+    pub fn commission_rate(&self) -> f64 {
+        self.commission_rate
+    }
+
+    /// Sets the fraction of rewards the validator keeps as commission before sharing the rest with
+    /// its delegators, clamped to `[0, 1]`.
+    pub fn set_commission_rate(&mut self, rate: f64) {
+        self.commission_rate = rate.clamp(0.0, 1.0);
+    }
+
     /// Returns whether the validator is configured to behave maliciously.
     pub fn is_malicious(&self) -> bool {
         self.is_malicious
     }
 
+    /// Returns how the validator misbehaves if it is malicious.
+    pub fn malicious_strategy(&self) -> MaliciousStrategy {
+        self.strategy
+    }
+
+    /// Sets how the validator misbehaves if it is malicious.
+    pub fn set_malicious_strategy(&mut self, strategy: MaliciousStrategy) {
+        self.strategy = strategy;
+    }
+
+    fn equivocates(&self) -> bool {
+        self.is_malicious && self.strategy == MaliciousStrategy::Equivocate
+    }
+
+    /// Returns the validator's record of validating other validators' proposals.
+    pub fn validation_stats(&self) -> ValidationStats {
+        self.validation_stats
+    }
+
+    /// Records one verdict this validator cast on another validator's proposal.
+    ///
+    /// A correct and timely verdict contributes 1 to the validator's pending validation
+    /// contribution, a correct but late one contributes 0.5, and an incorrect one nothing. The
+    /// pending contribution becomes the `v` term of the next score update.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{ConsensusEngine, PosygDcsEngine};
+    ///
+    /// let mut validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// validator.record_validation(true, true);
+    /// validator.record_validation(true, false);
+    /// validator.record_validation(false, true);
+    /// assert_eq!(validator.pending_validation(), 1.5);
+    /// validator.settle_validation();
+    /// assert!((validator.get_synergy_score() - 0.3).abs() < 1e-9);
+    /// assert_eq!(validator.validation_stats().correct_verdicts, 2);
+    /// ```
+    pub fn record_validation(&mut self, correct: bool, timely: bool) {
+        self.validation_stats.blocks_validated += 1;
+        if timely {
+            self.validation_stats.timely_votes += 1;
+        }
+        if correct {
+            self.validation_stats.correct_verdicts += 1;
+            self.pending_validation += if timely { 1.0 } else { 0.5 };
+        }
+    }
+
+    /// Returns the validation contribution not yet reflected in the synergy score.
+    pub fn pending_validation(&self) -> f64 {
+        self.pending_validation
+    }
+
+    /// Credits the pending validation contribution to the synergy score as the `v` term of DCS,
+    /// without touching the proposal-related terms.
+    ///
+    /// Validators that did not propose in a round are settled this way, so validation work is
+    /// rewarded as it happens rather than only when the validator next proposes.
+    pub fn settle_validation(&mut self) {
+        let v = std::mem::take(&mut self.pending_validation);
+        self.synergy_score += self.scoring.validation_delta(v);
+    }
+
+    /// Returns whether the validator is up, proposing and voting.
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+
+    /// Takes the validator down or brings it back up.
+    pub fn set_online(&mut self, online: bool) {
+        self.online = online;
+    }
+
+    /// Returns how long after the start of a round the validator's prevote reaches the network,
+    /// in milliseconds.
+    pub fn vote_delay_ms(&self) -> u64 {
+        self.vote_delay_ms
+    }
+
+    /// Sets how long after the start of a round the validator's prevote reaches the network.
+    ///
+    /// A prevote that arrives after the round's propose timeout still counts towards the tally,
+    /// but earns only half the validation credit of a timely one.
+    pub fn set_vote_delay_ms(&mut self, delay_ms: u64) {
+        self.vote_delay_ms = delay_ms;
+    }
+
+    /// Returns the number of rounds the validator has missed while offline.
+    pub fn downtime(&self) -> u64 {
+        self.downtime
+    }
+
+    /// Records a round the validator missed while offline, lowering its synergy score by the
+    /// scoring policy's downtime penalty.
+    pub fn record_downtime(&mut self) {
+        self.downtime += 1;
+        self.synergy_score += self.scoring.downtime_delta(1);
+    }
+
+    /// Returns the header of the latest block committed by the validator.
+    pub fn tip(&self) -> &BlockHeader {
+        &self.tip
+    }
+
+    /// Returns the public key the validator's proposals and votes are verified with.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Returns the registry of validator keys this validator verifies proposals against.
+    pub fn registry(&self) -> &ValidatorRegistry {
+        &self.registry
+    }
+
+    /// Registers the public key of another validator, so its proposals can be verified and
+    /// transactions may stake with it.
+    pub fn register_validator(&mut self, validator_id: String, key: VerifyingKey) {
+        self.accounts.add_validator(validator_id.clone());
+        self.registry.register(validator_id, key);
+    }
+
+    /// Returns the account state as of the validator's tip.
+    pub fn accounts(&self) -> &AccountState {
+        &self.accounts
+    }
+
+    /// Opens account `id` with signing key `key` and an initial `balance` in the validator's
+    /// account state.
+    ///
+    /// Every validator must open the same accounts, or they will disagree on which transactions
+    /// are valid.
+    pub fn open_account(&mut self, id: String, key: VerifyingKey, balance: u64) {
+        self.accounts.open(id, key, balance);
+    }
+
+    /// Proposes a signed block carrying `transactions` on top of the validator's tip.
+    ///
+    /// A malicious validator following [`MaliciousStrategy::InvalidBlock`] slips an unsigned
+    /// transaction in after them.
+    pub fn propose_block_with_transactions(
+        &self,
+        timestamp: u64,
+        mut transactions: Vec<Transaction>,
+    ) -> Result<Block, ConsensusError> {
+        if self.is_malicious && self.strategy == MaliciousStrategy::InvalidBlock {
+            let kind = TransactionKind::Transfer {
+                to: self.validator_id.clone(),
+                amount: 0,
+            };
+            transactions.push(Transaction::new(self.validator_id.clone(), 0, kind, 0));
+        }
+        let mut block = Block::new(
+            &self.tip,
+            self.validator_id.clone(),
+            timestamp,
+            transactions,
+        );
+        let mut accounts = self.accounts.clone();
+        accounts.apply_block(&block);
+        block.header.state_root = accounts.root();
+        block.sign(&self.signing_key);
+        Ok(block)
+    }
+
+    /// Casts a signed prevote for `block` in `round`.
+    ///
+    /// A validator always endorses its own proposal; any other block is prevoted only if it passes
+    /// `validate_block`, otherwise the validator prevotes nil.
+    pub fn prevote(&self, block: &Block, round: u32) -> Vote {
+        let endorse =
+            block.header.proposer == self.validator_id || self.validate_block(block).is_ok();
+        self.sign_vote(
+            VoteType::Prevote,
+            block.header.height,
+            round,
+            endorse.then(|| block.hash()),
+        )
+    }
+
+    /// Casts a second prevote in `round` that conflicts with the one returned by
+    /// [`prevote`](Self::prevote), if the validator equivocates.
+    ///
+    /// The proposer of `block` signs a conflicting block at the same height and prevotes for it;
+    /// any other validator prevotes the opposite verdict on `block`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{ConsensusEngine, MaliciousStrategy, PosygDcsEngine};
+    ///
+    /// let mut validator = PosygDcsEngine::new("v1".to_string(), 1000, true);
+    /// validator.set_malicious_strategy(MaliciousStrategy::Equivocate);
+    /// let block = validator.propose_block(1_000).unwrap();
+    /// let first = validator.prevote(&block, 0);
+    /// let second = validator.equivocating_prevote(&block, 0).unwrap();
+    /// assert_eq!(first.block_hash, Some(block.hash()));
+    /// assert!(second.block_hash.is_some() && second.block_hash != first.block_hash);
+    /// ```
+    pub fn equivocating_prevote(&self, block: &Block, round: u32) -> Option<Vote> {
+        if !self.equivocates() {
+            return None;
+        }
+        let block_hash = if block.header.proposer == self.validator_id {
+            Some(self.conflicting_block(block).hash())
+        } else {
+            self.prevote(block, round)
+                .block_hash
+                .xor(Some(block.hash()))
+        };
+        Some(self.sign_vote(VoteType::Prevote, block.header.height, round, block_hash))
+    }
+
+    /// Signs the header of `block` as this validator's proposal in `round`.
+    pub fn sign_header(&self, block: &Block, round: u32) -> SignedHeader {
+        SignedHeader::new(block.header.clone(), round, &self.signing_key)
+    }
+
+    /// Signs the header of a second block at the height of `block` as this validator's proposal
+    /// in `round`, if the validator equivocates and proposed `block`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::evidence::Evidence;
+    /// use peo_consensus::{ConsensusEngine, MaliciousStrategy, PosygDcsEngine};
+    ///
+    /// let mut validator = PosygDcsEngine::new("v1".to_string(), 1000, true);
+    /// validator.set_malicious_strategy(MaliciousStrategy::Equivocate);
+    /// let block = validator.propose_block(1_000).unwrap();
+    /// let second = validator.equivocating_proposal(&block, 0).unwrap();
+    /// assert!(Evidence::double_proposal(validator.sign_header(&block, 0), second).is_ok());
+    /// ```
+    pub fn equivocating_proposal(&self, block: &Block, round: u32) -> Option<SignedHeader> {
+        if !self.equivocates() || block.header.proposer != self.validator_id {
+            return None;
+        }
+        Some(self.sign_header(&self.conflicting_block(block), round))
+    }
+
+    /// Signs a block at the height of `block` that differs from it.
+    fn conflicting_block(&self, block: &Block) -> Block {
+        // A different timestamp is enough to make a different block at the same height.
+        let mut conflicting = Block::new(
+            &self.tip,
+            self.validator_id.clone(),
+            block.header.timestamp + 1,
+            Vec::new(),
+        );
+        conflicting.sign(&self.signing_key);
+        conflicting
+    }
+
+    /// Casts a signed nil prevote in `round` at `height` after timing out on the proposal: a vote
+    /// to skip the round's unresponsive proposer.
+    pub fn skip_prevote(&self, height: u64, round: u32) -> Vote {
+        self.sign_vote(VoteType::Prevote, height, round, None)
+    }
+
+    /// Casts a signed precommit for the block that gathered a supermajority of prevotes, or nil if
+    /// none did.
+    pub fn precommit(&self, height: u64, round: u32, polka: Option<Hash>) -> Vote {
+        self.sign_vote(VoteType::Precommit, height, round, polka)
+    }
+
+    fn sign_vote(
+        &self,
+        vote_type: VoteType,
+        height: u64,
+        round: u32,
+        block_hash: Option<Hash>,
+    ) -> Vote {
+        let mut vote = Vote {
+            vote_type,
+            height,
+            round,
+            block_hash,
+            validator: self.validator_id.clone(),
+            signature: None,
+        };
+        vote.sign(&self.signing_key);
+        vote
+    }
+
     /// Sets the validator's synergy score to a specific value.
     ///
     /// # Arguments
@@ -205,50 +748,57 @@ impl PosygDcsEngine {
 }
 
 impl ConsensusEngine for PosygDcsEngine {
-    fn propose_block(&self) -> Result<Block, ConsensusError> {
-        let block = if self.is_malicious {
-            Block {
-                id: 0,
-                proposer: self.validator_id.clone(),
-                transactions: vec!["invalid_tx".to_string()],
-            }
-        } else {
-            Block {
-                id: self.proposed_blocks + 1,
-                proposer: self.validator_id.clone(),
-                transactions: vec![],
-            }
-        };
-        Ok(block)
+    fn propose_block(&self, timestamp: u64) -> Result<Block, ConsensusError> {
+        self.propose_block_with_transactions(timestamp, Vec::new())
     }
 
     fn validate_block(&self, block: &Block) -> Result<(), ConsensusError> {
-        if block.transactions.contains(&"invalid_tx".to_string()) {
-            Err(ConsensusError::InvalidBlock)
-        } else {
-            Ok(())
+        let header = &block.header;
+        if header.height != self.tip.height + 1 {
+            return Err(ConsensusError::InvalidHeight);
+        }
+        if header.parent_hash != self.tip.hash() {
+            return Err(ConsensusError::InvalidParent);
+        }
+        if header.timestamp <= self.tip.timestamp {
+            return Err(ConsensusError::InvalidTimestamp);
+        }
+        block.verify_signature(&self.registry)?;
+        block.verify_contents()?;
+        let mut accounts = self.accounts.clone();
+        for tx in &block.transactions {
+            accounts.apply(tx, &header.proposer)?;
         }
+        if header.state_root != accounts.root() {
+            return Err(ConsensusError::InvalidStateRoot);
+        }
+        Ok(())
+    }
+
+    fn commit_block(&mut self, block: &Block) -> Result<(), ConsensusError> {
+        if block.header.height != self.tip.height + 1 {
+            return Err(ConsensusError::InvalidHeight);
+        }
+        if block.header.parent_hash != self.tip.hash() {
+            return Err(ConsensusError::InvalidParent);
+        }
+        self.tip = block.header.clone();
+        self.accounts.apply_block(block);
+        Ok(())
     }
 
     fn update_scores(&mut self, block_accepted: bool, violation_occurred: bool) {
-        const ALPHA: f64 = 0.4;
-        const BETA: f64 = 0.3;
-        const GAMMA: f64 = 0.2;
-        const DELTA: f64 = 0.5;
-
-        let h = if block_accepted { 1.0 } else { 0.0 };
-        let e = self.stake as f64 * 0.01;
-        let v = 0.0;
-        let p = if violation_occurred {
-            const BASE_PENALTY: f64 = 10.0;
-            const MULTIPLIER: f64 = 2.0;
+        if violation_occurred {
             self.violations += 1;
-            BASE_PENALTY * MULTIPLIER.powi(self.violations as i32 - 1)
-        } else {
-            0.0
+        }
+        let inputs = ScoreInputs {
+            block_accepted,
+            stake: self.stake,
+            validation: std::mem::take(&mut self.pending_validation),
+            violation_occurred,
+            violations: self.violations,
         };
-
-        self.synergy_score += ALPHA * h + BETA * e + GAMMA * v - DELTA * p;
+        self.synergy_score += self.scoring.proposal_delta(&inputs);
     }
 
     fn get_synergy_score(&self) -> f64 {
@@ -259,47 +809,1065 @@ impl ConsensusEngine for PosygDcsEngine {
 /// Manages a network of validators participating in the consensus process.
 ///
 /// This struct simulates a network where validators take turns proposing blocks based on a weighted
-/// random selection, reflecting their synergy scores and stakes.
+/// random selection, reflecting their synergy scores and stakes. Every proposal goes through a
+/// prevote and a precommit step; a block is finalized once more than two thirds of the voting
+/// weight precommits to it, at which point every validator commits it and the network keeps its
+/// [`QuorumCertificate`]. Finalized blocks enter a [`BlockTree`], weighted by their precommits, and
+/// the chain, balances, ledger and scores follow its canonical head. Only certified blocks enter
+/// the tree and each is finalized at once, so the head only ever moves forward and no effect is
+/// ever rolled back; a reverted block is reported as an invariant violation. Every validator knows
+/// the public keys of all others, and votes whose signatures do not verify are not counted. All
+/// randomness is drawn from the injected `rng`, so a network built from a fixed seed replays the
+/// exact same simulation.
 ///
 /// # Examples
 ///
 /// ```rust
 /// use peo_consensus::{Network, PosygDcsEngine, ConsensusEngine};
 ///
-/// let mut network = Network {
-///     validators: vec![
+/// let mut network = Network::from_seed(
+///     vec![
 ///         PosygDcsEngine::new("v1".to_string(), 1000, false),
 ///         PosygDcsEngine::new("v2".to_string(), 1500, false),
 ///     ],
-/// };
-/// network.run_consensus_round();
+///     42,
+/// );
+/// network.run_consensus_round().unwrap();
 /// let v1 = &network.validators[0];
 /// assert!(v1.proposed_blocks() <= 1);
+/// assert_eq!(network.head().header.height, 1);
+/// assert_eq!(network.head().header.parent_hash, network.chain()[0].hash());
+/// let certificate = network.certificate(1).unwrap();
+/// assert_eq!(certificate.block_hash, network.head().hash());
 /// ```
-pub struct Network {
+pub struct Network<R: RngCore = StdRng> {
     pub validators: Vec<PosygDcsEngine>,
+    rng: R,
+    chain: Vec<Block>,
+    tree: BlockTree,
+    round: u32,
+    vote_weighting: VoteWeighting,
+    certificates: BTreeMap<u64, QuorumCertificate>,
+    registry: ValidatorRegistry,
+    scoring: Arc<dyn ScoringPolicy>,
+    slashing: SlashingParams,
+    total_burned: u64,
+    epoch: u64,
+    epoch_length: u64,
+    unbonding_period: u64,
+    pending: Vec<PosygDcsEngine>,
+    leaving: Vec<String>,
+    unbonding: Vec<UnbondingValidator>,
+    unbonding_delegations: Vec<UnbondingDelegation>,
+    released: Vec<ReleasedStake>,
+    epochs: Vec<EpochSnapshot>,
+    issuance: IssuanceSchedule,
+    ledger: Ledger,
+    evidence: EvidencePool,
+    punished: HashSet<EvidenceKey>,
+    mempool: Mempool,
+    accounts: AccountState,
+    timeouts: TimeoutConfig,
+    clock_ms: u64,
+    events: EventBus,
 }
 
-impl Network {
-    /// Selects a validator to propose the next block based on a weighted random selection.
+impl Network<StdRng> {
+    /// Creates a network whose random number generator is seeded from operating system entropy.
+    pub fn new(validators: Vec<PosygDcsEngine>) -> Self {
+        Self::with_rng(validators, StdRng::from_entropy())
+    }
+
+    /// Creates a network whose random number generator is seeded from `seed`.
+    ///
+    /// Two networks built from the same validators and seed select the same proposers and
+    /// produce the same round outcomes.
+    pub fn from_seed(validators: Vec<PosygDcsEngine>, seed: u64) -> Self {
+        Self::with_rng(validators, StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<R: RngCore> Network<R> {
+    /// Creates a network that draws all of its randomness from the given generator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let validators = || vec![
+    ///     PosygDcsEngine::new("v1".to_string(), 1000, false),
+    ///     PosygDcsEngine::new("v2".to_string(), 1500, false),
+    /// ];
+    /// let mut a = Network::with_rng(validators(), StdRng::from_seed([7; 32]));
+    /// let mut b = Network::with_rng(validators(), StdRng::from_seed([7; 32]));
+    /// for _ in 0..10 {
+    ///     assert_eq!(a.select_proposer(), b.select_proposer());
+    /// }
+    /// ```
+    pub fn with_rng(mut validators: Vec<PosygDcsEngine>, rng: R) -> Self {
+        let mut registry = ValidatorRegistry::new();
+        let mut accounts = AccountState::new();
+        for validator in &validators {
+            registry.register(validator.validator_id.clone(), validator.verifying_key());
+            accounts.add_validator(validator.validator_id.clone());
+        }
+        for validator in &mut validators {
+            validator.registry = registry.clone();
+            validator.accounts = accounts.clone();
+        }
+        let genesis_epoch = EpochSnapshot {
+            epoch: 0,
+            start_height: 1,
+            active: validators.iter().map(|v| v.validator_id.clone()).collect(),
+            pending: Vec::new(),
+            leaving: Vec::new(),
+            unbonding: Vec::new(),
+        };
+        Self {
+            validators,
+            rng,
+            chain: vec![Block::genesis()],
+            tree: BlockTree::new(Block::genesis()),
+            round: 0,
+            vote_weighting: VoteWeighting::default(),
+            certificates: BTreeMap::new(),
+            registry,
+            scoring: Arc::new(DcsPolicy::default()),
+            slashing: SlashingParams::default(),
+            total_burned: 0,
+            epoch: 0,
+            epoch_length: DEFAULT_EPOCH_LENGTH,
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+            pending: Vec::new(),
+            leaving: Vec::new(),
+            unbonding: Vec::new(),
+            unbonding_delegations: Vec::new(),
+            released: Vec::new(),
+            epochs: vec![genesis_epoch],
+            issuance: IssuanceSchedule::default(),
+            ledger: Ledger::new(),
+            evidence: EvidencePool::new(),
+            punished: HashSet::new(),
+            mempool: Mempool::default(),
+            accounts,
+            timeouts: TimeoutConfig::default(),
+            clock_ms: 0,
+            events: EventBus::new(),
+        }
+    }
+
+    /// Returns how long validators wait for a proposal before skipping its proposer.
+    pub fn timeout_config(&self) -> &TimeoutConfig {
+        &self.timeouts
+    }
+
+    /// Sets how long validators wait for a proposal before skipping its proposer.
+    pub fn set_timeout_config(&mut self, config: TimeoutConfig) {
+        self.timeouts = config;
+    }
+
+    /// Returns the logical time elapsed since genesis, in milliseconds.
+    ///
+    /// A round with a live proposer takes [`BLOCK_INTERVAL_MS`]; a round whose proposer is offline
+    /// takes the round's propose timeout.
+    pub fn clock_ms(&self) -> u64 {
+        self.clock_ms
+    }
+
+    /// Takes the active validator `validator_id` down or brings it back up.
+    ///
+    /// An offline validator neither proposes nor votes, and every round it misses counts as
+    /// downtime against its synergy score. It still follows finalized blocks, as if it caught up
+    /// as soon as it came back.
+    pub fn set_online(&mut self, validator_id: &str, online: bool) -> Result<(), ConsensusError> {
+        self.validators
+            .iter_mut()
+            .find(|v| v.validator_id == validator_id)
+            .ok_or(ConsensusError::UnknownValidator)?
+            .set_online(online);
+        Ok(())
+    }
+
+    /// Returns the schedule by which the network mints rewards.
+    pub fn issuance_schedule(&self) -> &IssuanceSchedule {
+        &self.issuance
+    }
+
+    /// Sets the schedule by which the network mints rewards from the next finalized block on.
+    pub fn set_issuance_schedule(&mut self, schedule: IssuanceSchedule) {
+        self.issuance = schedule;
+    }
+
+    /// Returns the tokens issued so far, as initial balances and rewards.
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    /// Returns every delegation in the network, whether made through [`Network::delegate`] or by
+    /// a [`TransactionKind::Stake`] transaction.
+    pub fn delegations(&self) -> &DelegationBook {
+        self.accounts.delegations()
+    }
+
+    /// Delegates `amount` of `delegator`'s stake to the active or pending validator `validator_id`.
+    ///
+    /// Delegated stake counts towards the validator's weight in proposer selection and voting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// network.delegate("alice", "v1", 500).unwrap();
+    /// assert_eq!(network.validators[0].total_stake(), 1500);
+    /// assert!(network.delegate("alice", "v2", 500).is_err());
+    /// ```
+    pub fn delegate(
+        &mut self,
+        delegator: &str,
+        validator_id: &str,
+        amount: u64,
+    ) -> Result<(), ConsensusError> {
+        if !self.accounts.is_validator(validator_id) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        self.accounts
+            .delegations_mut()
+            .delegate(delegator, validator_id, amount)?;
+        self.sync_delegations();
+        Ok(())
+    }
+
+    /// Withdraws `amount` of the stake `delegator` delegated to `validator_id`.
+    ///
+    /// The stake stops counting towards the validator's weight right away. Stake withdrawn from an
+    /// active validator then stays locked, and slashable, until the validator would be released had
+    /// it unbonded in the same epoch; stake withdrawn from a validator that is still pending is
+    /// released immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// network.set_epoch_length(1);
+    /// network.set_unbonding_period(1);
+    /// network.delegate("alice", "v1", 500).unwrap();
+    /// network.undelegate("alice", "v1", 200).unwrap();
+    /// assert_eq!(network.validators[0].total_stake(), 1300);
+    /// assert_eq!(network.unbonding_delegations()[0].amount, 200);
+    ///
+    /// network.run_consensus_round().unwrap();
+    /// assert!(network.released_stake().is_empty());
+    /// network.run_consensus_round().unwrap();
+    /// let released = &network.released_stake()[0];
+    /// assert_eq!(released.validator, "v1");
+    /// assert_eq!(released.delegator.as_deref(), Some("alice"));
+    /// assert_eq!(released.amount, 200);
+    /// ```
+    pub fn undelegate(
+        &mut self,
+        delegator: &str,
+        validator_id: &str,
+        amount: u64,
+    ) -> Result<(), ConsensusError> {
+        if !self.accounts.is_validator(validator_id) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        let active = self
+            .validators
+            .iter()
+            .any(|v| v.validator_id == validator_id);
+        self.accounts
+            .delegations_mut()
+            .undelegate(delegator, validator_id, amount)?;
+        self.sync_delegations();
+        if active {
+            self.unbonding_delegations.push(UnbondingDelegation {
+                delegator: delegator.to_string(),
+                validator: validator_id.to_string(),
+                amount,
+                release_epoch: self.epoch + 1 + self.unbonding_period,
+            });
+        } else {
+            self.released.push(ReleasedStake {
+                validator: validator_id.to_string(),
+                delegator: Some(delegator.to_string()),
+                amount,
+                epoch: self.epoch,
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets the commission rate of the active or pending validator `validator_id`.
+    pub fn set_commission_rate(
+        &mut self,
+        validator_id: &str,
+        rate: f64,
+    ) -> Result<(), ConsensusError> {
+        self.validators
+            .iter_mut()
+            .chain(self.pending.iter_mut())
+            .find(|v| v.validator_id == validator_id)
+            .ok_or(ConsensusError::UnknownValidator)?
+            .set_commission_rate(rate);
+        Ok(())
+    }
+
+    /// Splits a `reward` earned by the active validator `validator_id` between the validator and
+    /// its delegators, according to its commission rate and their stakes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// network.delegate("alice", "v1", 3000).unwrap();
+    /// network.set_commission_rate("v1", 0.2).unwrap();
+    /// let split = network.split_reward("v1", 1000).unwrap();
+    /// assert_eq!(split.commission, 200);
+    /// assert_eq!(split.validator, 400);
+    /// assert_eq!(split.delegators, vec![("alice".to_string(), 600)]);
+    /// ```
+    pub fn split_reward(
+        &self,
+        validator_id: &str,
+        reward: u64,
+    ) -> Result<RewardSplit, ConsensusError> {
+        let validator = self
+            .validators
+            .iter()
+            .find(|v| v.validator_id == validator_id)
+            .ok_or(ConsensusError::UnknownValidator)?;
+        Ok(delegation::split_reward(
+            reward,
+            validator.stake,
+            validator.commission_rate,
+            &self.delegations().delegations_to(validator_id),
+        ))
+    }
+
+    /// Returns the current epoch, starting at 0.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the number of blocks in an epoch.
+    pub fn epoch_length(&self) -> u64 {
+        self.epoch_length
+    }
+
+    /// Sets the number of blocks in an epoch. Validator-set changes take effect after every
+    /// finalized height that is a multiple of it.
+    pub fn set_epoch_length(&mut self, epoch_length: u64) {
+        self.epoch_length = epoch_length.max(1);
+    }
+
+    /// Returns the number of epochs stake stays locked after a validator leaves.
+    pub fn unbonding_period(&self) -> u64 {
+        self.unbonding_period
+    }
+
+    /// Sets the number of epochs stake stays locked after a validator leaves.
+    pub fn set_unbonding_period(&mut self, epochs: u64) {
+        self.unbonding_period = epochs;
+    }
+
+    /// Returns the validators taking part in consensus in the current epoch.
+    pub fn active_validators(&self) -> &[PosygDcsEngine] {
+        &self.validators
+    }
+
+    /// Returns the validators that bonded stake and join at the next epoch boundary.
+    pub fn pending_validators(&self) -> &[PosygDcsEngine] {
+        &self.pending
+    }
+
+    /// Returns the identifiers of active validators that leave at the next epoch boundary.
+    pub fn leaving_validators(&self) -> &[String] {
+        &self.leaving
+    }
+
+    /// Returns the validators whose stake is locked while they unbond.
+    pub fn unbonding_validators(&self) -> &[UnbondingValidator] {
+        &self.unbonding
+    }
+
+    /// Returns the stake delegators withdrew from active validators and that is still locked.
+    pub fn unbonding_delegations(&self) -> &[UnbondingDelegation] {
+        &self.unbonding_delegations
+    }
+
+    /// Returns the stake released to validators and delegators at the end of their unbonding
+    /// period.
+    pub fn released_stake(&self) -> &[ReleasedStake] {
+        &self.released
+    }
+
+    /// Returns the validator sets of `epoch`, if it has started.
+    pub fn epoch_snapshot(&self, epoch: u64) -> Option<&EpochSnapshot> {
+        self.epochs.get(epoch as usize)
+    }
+
+    /// Bonds a new validator, which joins the active set at the next epoch boundary.
+    ///
+    /// # Examples
     ///
-    /// The weight is calculated as the sum of the validator's synergy score and a fraction of its stake.
-    pub fn select_proposer(&self) -> usize {
-        let total_weight: f64 = self.validators
+    /// ```rust
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// network.set_epoch_length(2);
+    /// network.bond_validator(PosygDcsEngine::new("v2".to_string(), 500, false)).unwrap();
+    /// assert_eq!(network.pending_validators().len(), 1);
+    ///
+    /// network.run_consensus_round().unwrap();
+    /// assert_eq!(network.active_validators().len(), 1);
+    /// network.run_consensus_round().unwrap();
+    /// assert_eq!(network.epoch(), 1);
+    /// assert_eq!(network.active_validators().len(), 2);
+    /// assert_eq!(network.epoch_snapshot(0).unwrap().pending, vec!["v2"]);
+    /// ```
+    pub fn bond_validator(&mut self, validator: PosygDcsEngine) -> Result<(), ConsensusError> {
+        if validator.stake == 0 {
+            return Err(ConsensusError::InvalidStake);
+        }
+        if self.is_known_validator(&validator.validator_id) {
+            return Err(ConsensusError::DuplicateValidator);
+        }
+        self.accounts.add_validator(validator.validator_id.clone());
+        for engine in self.validators.iter_mut().chain(self.pending.iter_mut()) {
+            engine
+                .accounts
+                .add_validator(validator.validator_id.clone());
+        }
+        self.pending.push(validator);
+        self.refresh_epoch_snapshot();
+        Ok(())
+    }
+
+    /// Starts unbonding the validator `validator_id`.
+    ///
+    /// An active validator keeps validating until the next epoch boundary and its stake then stays
+    /// locked, and slashable, for the unbonding period. A validator that is still pending is
+    /// removed right away and its stake released. Stake delegated to the validator is released
+    /// along with its own.
+    pub fn unbond_validator(&mut self, validator_id: &str) -> Result<(), ConsensusError> {
+        if let Some(position) = self
+            .pending
+            .iter()
+            .position(|v| v.validator_id == validator_id)
+        {
+            let validator = self.pending.remove(position);
+            self.release_stake(validator);
+            self.refresh_epoch_snapshot();
+            return Ok(());
+        }
+        if !self
+            .validators
+            .iter()
+            .any(|v| v.validator_id == validator_id)
+            || self.leaving.iter().any(|id| id == validator_id)
+        {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        // Pending validators may still unbond before they join, so only active ones count.
+        if self.leaving.len() + 1 >= self.validators.len() {
+            return Err(ConsensusError::EmptyValidatorSet);
+        }
+        self.leaving.push(validator_id.to_string());
+        self.refresh_epoch_snapshot();
+        Ok(())
+    }
+
+    fn is_known_validator(&self, validator_id: &str) -> bool {
+        self.validators
             .iter()
-            .map(|v| v.get_synergy_score() + v.stake as f64 * 0.01)
-            .sum();
+            .any(|v| v.validator_id == validator_id)
+            || self.pending.iter().any(|v| v.validator_id == validator_id)
+            || self
+                .unbonding
+                .iter()
+                .any(|u| u.engine.validator_id == validator_id)
+    }
+
+    /// Applies the validator-set changes queued during the epoch that just ended.
+    fn transition_epoch(&mut self) {
+        self.distribute_epoch_reward();
+        self.epoch += 1;
+
+        let leaving = std::mem::take(&mut self.leaving);
+        let (departed, staying): (Vec<_>, Vec<_>) = std::mem::take(&mut self.validators)
+            .into_iter()
+            .partition(|v| leaving.contains(&v.validator_id));
+        self.validators = staying;
+        for validator in &departed {
+            self.retire_validator(&validator.validator_id);
+        }
+        let release_epoch = self.epoch + self.unbonding_period;
+        self.unbonding
+            .extend(departed.into_iter().map(|engine| UnbondingValidator {
+                engine,
+                release_epoch,
+            }));
+
+        let epoch = self.epoch;
+        let (matured, locked): (Vec<_>, Vec<_>) = std::mem::take(&mut self.unbonding)
+            .into_iter()
+            .partition(|u| u.release_epoch <= epoch);
+        self.unbonding = locked;
+        for unbonded in matured {
+            self.release_stake(unbonded.engine);
+        }
+        let (matured, locked): (Vec<_>, Vec<_>) = std::mem::take(&mut self.unbonding_delegations)
+            .into_iter()
+            .partition(|u| u.release_epoch <= epoch);
+        self.unbonding_delegations = locked;
+        self.released
+            .extend(matured.into_iter().map(|unbonded| ReleasedStake {
+                validator: unbonded.validator,
+                delegator: Some(unbonded.delegator),
+                amount: unbonded.amount,
+                epoch,
+            }));
+
+        let tip = self.head().header.clone();
+        for mut validator in std::mem::take(&mut self.pending) {
+            validator.tip = tip.clone();
+            validator.accounts = self.accounts.clone();
+            validator.set_scoring_policy(self.scoring.clone());
+            self.registry
+                .register(validator.validator_id.clone(), validator.verifying_key());
+            self.validators.push(validator);
+        }
+
+        let mut active_keys = ValidatorRegistry::new();
+        for validator in &self.validators {
+            active_keys.register(validator.validator_id.clone(), validator.verifying_key());
+        }
+        for validator in &mut self.validators {
+            validator.registry = active_keys.clone();
+        }
+
+        self.epochs.push(EpochSnapshot {
+            epoch,
+            start_height: tip.height + 1,
+            active: Vec::new(),
+            pending: Vec::new(),
+            leaving: Vec::new(),
+            unbonding: Vec::new(),
+        });
+        self.refresh_epoch_snapshot();
+    }
+
+    /// Mints the reward of the epoch ending at the head and shares it among the epoch's active
+    /// validators, each of which splits its part with its delegators.
+    fn distribute_epoch_reward(&mut self) {
+        let reward = self.issuance.epoch_reward_at(self.head().header.height);
+        let weights: Vec<u64> = self
+            .validators
+            .iter()
+            .map(|v| {
+                if self.issuance.synergy_scaled {
+                    // Selection weights are fractional; share in thousandths of a weight unit.
+                    (selection_weight(v) * 1000.0) as u64
+                } else {
+                    v.total_stake()
+                }
+            })
+            .collect();
+        let shares = slashing::distribute_pro_rata(reward, &weights);
+        let mut credits = Vec::new();
+        for (validator, share) in self.validators.iter().zip(shares) {
+            let split = delegation::split_reward(
+                share,
+                validator.stake,
+                validator.commission_rate,
+                &self.delegations().delegations_to(&validator.validator_id),
+            );
+            credits.push((validator.validator_id.clone(), split.validator));
+            credits.extend(split.delegators);
+        }
+        for (account, amount) in credits {
+            self.mint(&account, amount);
+        }
+    }
+
+    /// Mints `amount` new tokens into the spendable balance of account `id`, on the network and on
+    /// every validator, and records them in the ledger.
+    fn mint(&mut self, id: &str, amount: u64) {
+        self.ledger.mint(id, amount);
+        self.accounts.credit(id, amount);
+        for validator in self.validators.iter_mut().chain(self.pending.iter_mut()) {
+            validator.accounts.credit(id, amount);
+        }
+    }
+
+    /// Copies the network's delegations to every validator's account state and recomputes the
+    /// stake delegated to each validator from them.
+    fn sync_delegations(&mut self) {
+        let book = self.accounts.delegations();
+        for validator in self
+            .validators
+            .iter_mut()
+            .chain(self.pending.iter_mut())
+            .chain(self.unbonding.iter_mut().map(|u| &mut u.engine))
+        {
+            validator.delegated_stake = book.delegated_to(&validator.validator_id);
+            validator.accounts.delegations_mut().clone_from(book);
+        }
+    }
+
+    /// Stops the validator `validator_id` from accepting stake, on the network and on every
+    /// validator.
+    fn retire_validator(&mut self, validator_id: &str) {
+        self.accounts.remove_validator(validator_id);
+        for validator in self.validators.iter_mut().chain(self.pending.iter_mut()) {
+            validator.accounts.remove_validator(validator_id);
+        }
+    }
+
+    /// Returns the stake of a departing validator, and of its delegators, to their owners.
+    fn release_stake(&mut self, validator: PosygDcsEngine) {
+        let epoch = self.epoch;
+        self.retire_validator(&validator.validator_id);
+        let delegations = self
+            .accounts
+            .delegations_mut()
+            .remove_validator(&validator.validator_id);
+        self.sync_delegations();
+        for delegation in delegations {
+            self.released.push(ReleasedStake {
+                validator: validator.validator_id.clone(),
+                delegator: Some(delegation.delegator),
+                amount: delegation.amount,
+                epoch,
+            });
+        }
+        self.released.push(ReleasedStake {
+            validator: validator.validator_id,
+            delegator: None,
+            amount: validator.stake,
+            epoch,
+        });
+    }
+
+    fn refresh_epoch_snapshot(&mut self) {
+        let snapshot = self
+            .epochs
+            .last_mut()
+            .expect("the genesis epoch is always recorded");
+        snapshot.active = self
+            .validators
+            .iter()
+            .map(|v| v.validator_id.clone())
+            .collect();
+        snapshot.pending = self
+            .pending
+            .iter()
+            .map(|v| v.validator_id.clone())
+            .collect();
+        snapshot.leaving = self.leaving.clone();
+        snapshot.unbonding = self
+            .unbonding
+            .iter()
+            .map(|u| u.engine.validator_id.clone())
+            .collect();
+    }
+
+    /// Sets how much stake each kind of violation costs, and whether it is burned or redistributed.
+    pub fn set_slashing_params(&mut self, params: SlashingParams) {
+        self.slashing = params;
+    }
+
+    /// Returns the network's slashing parameters.
+    pub fn slashing_params(&self) -> SlashingParams {
+        self.slashing
+    }
+
+    /// Returns the total stake burned by slashing.
+    pub fn total_burned(&self) -> u64 {
+        self.total_burned
+    }
+
+    /// Slashes the active or unbonding validator `validator_id` for `reason`, returning the amount
+    /// of stake taken.
+    ///
+    /// The same fraction is taken from the validator's own stake and from every delegation to it.
+    /// The slashed stake is burned or shared among the other validators in proportion to their own
+    /// stake, according to the network's [`SlashingParams`]. Less stake means less weight in
+    /// proposer selection and voting.
+    ///
+    /// Fails with [`ConsensusError::UnknownValidator`] if `validator_id` is neither active nor
+    /// unbonding.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::slashing::{SlashDestination, SlashReason, SlashingParams};
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let mut network = Network::from_seed(vec![
+    ///     PosygDcsEngine::new("v1".to_string(), 1000, false),
+    ///     PosygDcsEngine::new("v2".to_string(), 1000, false),
+    ///     PosygDcsEngine::new("v3".to_string(), 2000, false),
+    /// ], 1);
+    /// network.set_slashing_params(SlashingParams {
+    ///     destination: SlashDestination::Redistribute,
+    ///     ..SlashingParams::default()
+    /// });
+    /// assert_eq!(network.slash("v1", SlashReason::Equivocation).unwrap(), 50);
+    /// assert!(network.slash("v4", SlashReason::Equivocation).is_err());
+    /// assert_eq!(network.validators[0].stake(), 950);
+    /// assert_eq!(network.validators[1].stake(), 1016);
+    /// assert_eq!(network.validators[2].stake(), 2034);
+    /// ```
+    pub fn slash(
+        &mut self,
+        validator_id: &str,
+        reason: SlashReason,
+    ) -> Result<u64, ConsensusError> {
+        let known = self
+            .validators
+            .iter()
+            .chain(self.unbonding.iter().map(|u| &u.engine))
+            .any(|v| v.validator_id == validator_id);
+        if !known {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        Ok(self.slash_validator(validator_id, reason))
+    }
+
+    /// Returns the transactions waiting to be included in a block.
+    pub fn mempool(&self) -> &Mempool {
+        &self.mempool
+    }
+
+    /// Replaces the mempool's limits, keeping as many pending transactions as fit, highest fee
+    /// first.
+    pub fn set_mempool_config(&mut self, config: MempoolConfig) {
+        let previous = std::mem::replace(&mut self.mempool, Mempool::new(config));
+        for pending in previous.iter() {
+            // A transaction that no longer fits is dropped.
+            let _ = self.mempool.submit(pending.transaction.clone());
+        }
+    }
+
+    /// Returns the account state as of the chain head.
+    pub fn accounts(&self) -> &AccountState {
+        &self.accounts
+    }
+
+    /// Opens account `id` with signing key `key` and an initial `balance` on every validator.
+    ///
+    /// The initial balance is new tokens, so it counts towards the ledger's total supply.
+    pub fn open_account(&mut self, id: &str, key: VerifyingKey, balance: u64) {
+        self.ledger.mint(id, balance);
+        self.accounts.open(id.to_string(), key, balance);
+        for validator in self.validators.iter_mut().chain(self.pending.iter_mut()) {
+            validator.open_account(id.to_string(), key, balance);
+        }
+    }
+
+    /// Submits `transaction` to the mempool, returning its hash.
+    ///
+    /// The transaction must be signed by its sender's key and carry a nonce the sender has not
+    /// used yet. Proposers include the highest-paying pending transactions that apply in their
+    /// blocks, and a transaction leaves the mempool once a block including it is finalized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::crypto::SigningKey;
+    /// use peo_consensus::transaction::{Transaction, TransactionKind};
+    /// use peo_consensus::{Network, PosygDcsEngine};
+    ///
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// let key = SigningKey::from_bytes(&[1; 32]);
+    /// network.open_account("alice", key.verifying_key(), 100);
+    ///
+    /// let kind = TransactionKind::Transfer { to: "bob".to_string(), amount: 30 };
+    /// let mut tx = Transaction::new("alice".to_string(), 0, kind, 2);
+    /// tx.sign(&key);
+    /// network.submit_transaction(tx.clone()).unwrap();
+    /// assert!(network.submit_transaction(tx.clone()).is_err());
+    ///
+    /// network.run_consensus_round().unwrap();
+    /// assert_eq!(network.head().transactions, vec![tx]);
+    /// assert_eq!(network.accounts().balance("bob"), 30);
+    /// // The proposer earns the fee on top of the block reward.
+    /// assert_eq!(network.accounts().balance("v1"), 2 + 10);
+    /// assert!(network.mempool().is_empty());
+    /// ```
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<Hash, ConsensusError> {
+        self.accounts.check_admissible(&transaction)?;
+        self.mempool.submit(transaction)
+    }
+
+    /// Returns the evidence pool that watches the network's votes and proposals for equivocation.
+    pub fn evidence_pool(&self) -> &EvidencePool {
+        &self.evidence
+    }
+
+    /// Submits `evidence` of equivocation, slashing the offender and returning the amount taken.
+    ///
+    /// Any validator may submit evidence. It must verify against the network's registry, concern an
+    /// active or unbonding validator and be at most [`EVIDENCE_MAX_AGE`] heights behind the next
+    /// one, and each violation is punished only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::crypto::SigningKey;
+    /// use peo_consensus::evidence::Evidence;
+    /// use peo_consensus::{ConsensusEngine, ConsensusError, Network, PosygDcsEngine};
+    ///
+    /// let key = SigningKey::from_bytes(&[7; 32]);
+    /// let mut network = Network::from_seed(
+    ///     vec![
+    ///         PosygDcsEngine::with_signing_key("v1".to_string(), 1000, false, key.clone()),
+    ///         PosygDcsEngine::new("v2".to_string(), 1000, false),
+    ///     ],
+    ///     1,
+    /// );
+    /// let block = network.validators[0].propose_block(1_000).unwrap();
+    /// let first = network.validators[0].prevote(&block, 0);
+    /// let mut second = first.clone();
+    /// second.block_hash = None;
+    /// second.sign(&key);
+    ///
+    /// let evidence = Evidence::new(first, second).unwrap();
+    /// assert_eq!(network.submit_evidence(evidence.clone()).unwrap(), 50);
+    /// let duplicate = network.submit_evidence(evidence);
+    /// assert!(matches!(duplicate, Err(ConsensusError::DuplicateEvidence)));
+    /// ```
+    pub fn submit_evidence(&mut self, evidence: Evidence) -> Result<u64, ConsensusError> {
+        evidence.verify(&self.registry)?;
+        if evidence.height() < self.evidence_horizon() {
+            return Err(ConsensusError::ExpiredEvidence);
+        }
+        let offender = evidence.validator().to_string();
+        let slashable = self.validators.iter().any(|v| v.validator_id == offender)
+            || self
+                .unbonding
+                .iter()
+                .any(|u| u.engine.validator_id == offender);
+        if !slashable {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        if !self.punished.insert(evidence.key()) {
+            return Err(ConsensusError::DuplicateEvidence);
+        }
+        Ok(self.slash_validator(&offender, SlashReason::Equivocation))
+    }
+
+    /// Returns the lowest height evidence is still accepted and remembered for.
+    fn evidence_horizon(&self) -> u64 {
+        (self.head().header.height + 1).saturating_sub(EVIDENCE_MAX_AGE)
+    }
+
+    /// Slashes the active or unbonding validator `validator_id`, if any; see [`Network::slash`].
+    fn slash_validator(&mut self, validator_id: &str, reason: SlashReason) -> u64 {
+        let height = self.head().header.height;
+        let Some(validator) = self
+            .validators
+            .iter_mut()
+            .chain(self.unbonding.iter_mut().map(|u| &mut u.engine))
+            .find(|v| v.validator_id == validator_id)
+        else {
+            return 0;
+        };
+        let own = self.slashing.penalty(reason, validator.stake);
+        let own = validator.slash(reason, own, height);
+        let fraction = self.slashing.fraction(reason);
+        let delegated = self
+            .accounts
+            .delegations_mut()
+            .slash(validator_id, fraction);
+        let mut unbonding = 0;
+        for withdrawn in self
+            .unbonding_delegations
+            .iter_mut()
+            .filter(|u| u.validator == validator_id)
+        {
+            let cut = (withdrawn.amount as f64 * fraction.clamp(0.0, 1.0)).floor() as u64;
+            withdrawn.amount -= cut;
+            unbonding += cut;
+        }
+        let delegated = delegated + unbonding;
+        if let Some(record) = validator.slash_history.last_mut() {
+            record.delegated_amount = delegated;
+        }
+        self.sync_delegations();
+        let amount = own + delegated;
 
-        if total_weight == 0.0 {
-            return thread_rng().gen_range(0..self.validators.len());
+        match self.slashing.destination {
+            SlashDestination::Burn => self.total_burned += amount,
+            SlashDestination::Redistribute => {
+                let stakes: Vec<u64> = self
+                    .validators
+                    .iter()
+                    .map(|v| {
+                        if v.validator_id == validator_id {
+                            0
+                        } else {
+                            v.stake
+                        }
+                    })
+                    .collect();
+                let shares = slashing::distribute_pro_rata(amount, &stakes);
+                if shares.iter().sum::<u64>() < amount {
+                    // Nobody else holds stake, so there is no one to redistribute to.
+                    self.total_burned += amount;
+                }
+                for (validator, share) in self.validators.iter_mut().zip(shares) {
+                    validator.add_stake(share);
+                }
+            }
         }
+        self.events.emit(ConsensusEvent::ViolationRecorded {
+            validator: validator_id.to_string(),
+            height,
+            reason,
+            slashed: amount,
+        });
+        amount
+    }
+
+    /// Subscribes `observer` to the [`ConsensusEvent`]s of every following round.
+    pub fn subscribe(&mut self, observer: Arc<dyn Observer>) {
+        self.events.subscribe(observer);
+    }
 
-        let mut rng = thread_rng();
-        let random_weight = rng.gen_range(0.0..total_weight);
+    /// Sets the formula every validator in the network uses to update its synergy score.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use peo_consensus::scoring::{DcsParams, DcsPolicy};
+    /// use peo_consensus::{ConsensusEngine, Network, PosygDcsEngine};
+    /// use std::sync::Arc;
+    ///
+    /// let params = DcsParams { alpha: 1.0, beta: 0.0, ..DcsParams::default() };
+    /// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+    /// let mut network = Network::from_seed(vec![validator], 1);
+    /// network.set_scoring_policy(Arc::new(DcsPolicy::new(params)));
+    /// network.run_consensus_round().unwrap();
+    /// assert_eq!(network.validators[0].get_synergy_score(), 1.0);
+    /// ```
+    pub fn set_scoring_policy(&mut self, policy: Arc<dyn ScoringPolicy>) {
+        for validator in &mut self.validators {
+            validator.set_scoring_policy(policy.clone());
+        }
+        self.scoring = policy;
+    }
+
+    /// Returns the formula validators in the network use to update their synergy scores.
+    pub fn scoring_policy(&self) -> &Arc<dyn ScoringPolicy> {
+        &self.scoring
+    }
+
+    /// Returns the public keys of all validators in the network.
+    pub fn registry(&self) -> &ValidatorRegistry {
+        &self.registry
+    }
+
+    /// Sets how validators' votes are weighted when counting quorums.
+    pub fn set_vote_weighting(&mut self, vote_weighting: VoteWeighting) {
+        self.vote_weighting = vote_weighting;
+    }
+
+    /// Returns how validators' votes are weighted when counting quorums.
+    pub fn vote_weighting(&self) -> VoteWeighting {
+        self.vote_weighting
+    }
+
+    /// Returns the voting power of the validator at `index` under the current weighting.
+    pub fn voting_power(&self, index: usize) -> f64 {
+        let validator = &self.validators[index];
+        match self.vote_weighting {
+            VoteWeighting::Stake => validator.total_stake() as f64,
+            VoteWeighting::StakeAndSynergy => selection_weight(validator),
+        }
+    }
+
+    /// Returns the combined voting power of all validators.
+    pub fn total_voting_power(&self) -> f64 {
+        (0..self.validators.len())
+            .map(|i| self.voting_power(i))
+            .sum()
+    }
+
+    /// Returns the round currently being run at the next height.
+    ///
+    /// The round starts at 0 for every height and advances each time a round ends without
+    /// finalizing a block.
+    pub fn current_round(&self) -> u32 {
+        self.round
+    }
+
+    /// Returns the quorum certificate that finalized the block at `height`.
+    pub fn certificate(&self, height: u64) -> Option<&QuorumCertificate> {
+        self.certificates.get(&height)
+    }
+
+    /// Returns the quorum certificates of all finalized heights, keyed by height.
+    pub fn certificates(&self) -> &BTreeMap<u64, QuorumCertificate> {
+        &self.certificates
+    }
+
+    /// Returns the accepted chain, starting with the genesis block.
+    pub fn chain(&self) -> &[Block] {
+        &self.chain
+    }
+
+    /// Returns the latest accepted block.
+    pub fn head(&self) -> &Block {
+        self.chain
+            .last()
+            .expect("chain always contains the genesis block")
+    }
+
+    /// Returns the tree of accepted blocks descending from the last finalized one, whose
+    /// canonical chain the network follows.
+    pub fn block_tree(&self) -> &BlockTree {
+        &self.tree
+    }
+
+    /// Returns a mutable reference to the network's random number generator.
+    pub fn rng_mut(&mut self) -> &mut R {
+        &mut self.rng
+    }
+
+    /// Selects a validator to propose the next block based on a weighted random selection.
+    ///
+    /// The weight is calculated by the validators' scoring policy as the sum of the validator's
+    /// synergy score and a fraction of its stake, floored at zero. If no validator has any weight,
+    /// the proposer is chosen uniformly.
+    pub fn select_proposer(&mut self) -> usize {
+        let total_weight: f64 = self.validators.iter().map(selection_weight).sum();
+
+        if total_weight <= 0.0 {
+            return self.rng.gen_range(0..self.validators.len());
+        }
+
+        let random_weight = self.rng.gen_range(0.0..total_weight);
 
         let mut cumulative_weight = 0.0;
         for (i, validator) in self.validators.iter().enumerate() {
-            cumulative_weight += validator.get_synergy_score() + validator.stake as f64 * 0.01;
+            cumulative_weight += selection_weight(validator);
             if cumulative_weight >= random_weight {
                 return i;
             }
@@ -309,32 +1877,380 @@ impl Network {
 
     /// Executes a single round of the consensus protocol.
     ///
-    /// A validator is selected to propose a block, which is then validated by others, and scores are updated.
-    pub fn run_consensus_round(&mut self) {
-        let proposer_index = self.select_proposer();
-        let (block, is_malicious);
+    /// A validator is selected to propose a block on top of the chain head. Every validator then
+    /// prevotes for the block if it is valid, or nil otherwise; if more than two thirds of the
+    /// voting weight prevoted for it, validators precommit to it, and a supermajority of
+    /// precommits finalizes the block. The proposer is penalized, and slashed for an invalid block,
+    /// when a blocking third of the voting weight prevoted nil, since the block can then never be
+    /// finalized.
+    ///
+    /// Every other validator whose prevote was counted is credited for its validation work: its
+    /// verdict is correct if it prevoted a block that got finalized, or nil on one that did not.
+    ///
+    /// Conflicting prevotes are caught by the network's [`EvidencePool`] and the evidence is
+    /// submitted right away, slashing the equivocating validator.
+    ///
+    /// The proposer fills its block from the network's [`Mempool`], and the transactions of a
+    /// finalized block are applied to the account state and leave the mempool.
+    ///
+    /// The proposer of a finalized block is paid the block reward of the [`IssuanceSchedule`], and
+    /// the block that ends an epoch also pays out the epoch reward.
+    ///
+    /// Offline validators neither propose nor vote, and are charged a round of downtime. If the
+    /// proposer is offline, the others wait out the round's propose timeout and vote to skip it.
+    /// Once more than two thirds of the voting power has voted to skip, the proposer is slashed for
+    /// downtime and the next round gets a longer timeout, per the network's [`TimeoutConfig`];
+    /// with fewer skip votes the round is played again.
+    ///
+    /// Observers subscribed with [`Network::subscribe`] are told who proposed, what became of the
+    /// block, which validators were slashed and whose synergy score changed; the returned
+    /// [`RoundOutcome`] sums up the same.
+    ///
+    /// Fails with [`ConsensusError::EmptyValidatorSet`] if the network has no validator, before the
+    /// round changes any state, or with the error of a proposer that cannot build its block. By
+    /// then the proposer has been drawn, which advances the random number generator, so the next
+    /// round may select another proposer; nothing else has changed.
+    /// Fails with [`ConsensusError::InvariantViolated`] if a block that gathered a quorum of
+    /// precommits cannot be committed, which would mean validators no longer share the network's
+    /// head; the network is then left halfway through the commit and should be discarded.
+    pub fn run_consensus_round(&mut self) -> Result<RoundOutcome, ConsensusError> {
+        if self.validators.is_empty() {
+            return Err(ConsensusError::EmptyValidatorSet);
+        }
+        let scores: BTreeMap<String, f64> = self
+            .validators
+            .iter()
+            .map(|v| (v.validator_id.clone(), v.synergy_score))
+            .collect();
+        let slashes: BTreeMap<String, usize> = self
+            .validators
+            .iter()
+            .map(|v| (v.validator_id.clone(), v.slash_history.len()))
+            .collect();
+        let mut outcome = self.play_round()?;
+        for validator in &self.validators {
+            let Some(&previous) = scores.get(&validator.validator_id) else {
+                continue;
+            };
+            outcome.score_deltas.insert(
+                validator.validator_id.clone(),
+                validator.synergy_score - previous,
+            );
+            if previous != validator.synergy_score {
+                self.events.emit(ConsensusEvent::ScoreUpdated {
+                    validator: validator.validator_id.clone(),
+                    previous,
+                    score: validator.synergy_score,
+                });
+            }
+        }
+        // A validator slashed in the round may have left the active set at an epoch boundary.
+        for validator in self
+            .validators
+            .iter()
+            .chain(self.unbonding.iter().map(|u| &u.engine))
         {
-            let proposer = &mut self.validators[proposer_index];
-            block = proposer.propose_block().unwrap();
-            is_malicious = proposer.is_malicious();
+            if let Some(&before) = slashes.get(&validator.validator_id) {
+                let records = validator.slash_history.iter().skip(before);
+                outcome
+                    .violations
+                    .extend(records.map(|record| (validator.validator_id.clone(), *record)));
+            }
         }
+        Ok(outcome)
+    }
 
-        let mut is_valid = true;
-        for (i, validator) in self.validators.iter().enumerate() {
-            if i != proposer_index {
-                if let Err(_) = validator.validate_block(&block) {
-                    is_valid = false;
-                    break;
+    /// Runs a round, leaving the score deltas and violations of its outcome to the caller.
+    fn play_round(&mut self) -> Result<RoundOutcome, ConsensusError> {
+        let proposer_index = self.select_proposer();
+        let timestamp = self.head().header.timestamp + BLOCK_INTERVAL_MS;
+        let height = self.head().header.height + 1;
+        let round = self.round;
+        // The only step that can fail comes before any state changes besides the proposer draw, so
+        // a failed round leaves the network as it was but for its random number generator.
+        let proposal = {
+            let proposer = &self.validators[proposer_index];
+            if proposer.online {
+                let transactions = self
+                    .mempool
+                    .select(&proposer.accounts, &proposer.validator_id);
+                Some(proposer.propose_block_with_transactions(timestamp, transactions)?)
+            } else {
+                None
+            }
+        };
+        for validator in self.validators.iter_mut().filter(|v| !v.online) {
+            validator.record_downtime();
+        }
+        self.events.emit(ConsensusEvent::ProposerSelected {
+            height,
+            round,
+            proposer: self.validators[proposer_index].validator_id.clone(),
+            online: self.validators[proposer_index].online,
+        });
+        let mut outcome = RoundOutcome {
+            height,
+            round,
+            proposer: self.validators[proposer_index].validator_id.clone(),
+            block_hash: None,
+            finalized: false,
+            verdicts: self
+                .validators
+                .iter()
+                .map(|v| (v.validator_id.clone(), Verdict::Absent))
+                .collect(),
+            score_deltas: BTreeMap::new(),
+            violations: Vec::new(),
+        };
+        let Some(block) = proposal else {
+            self.skip_round(proposer_index, height, round, &mut outcome.verdicts);
+            return Ok(outcome);
+        };
+        // A prevote is timely if it arrives before the round's propose timeout expires.
+        let round_start_ms = self.clock_ms;
+        let deadline_ms = round_start_ms + self.timeouts.propose_timeout(round);
+        self.clock_ms += BLOCK_INTERVAL_MS;
+        self.events.emit(ConsensusEvent::BlockProposed {
+            height,
+            round,
+            proposer: outcome.proposer.clone(),
+            hash: block.hash(),
+            transactions: block.transactions.len(),
+        });
+        // A proposer that signs two blocks for the round is caught like a double vote.
+        self.evidence
+            .observe_proposal(&self.validators[proposer_index].sign_header(&block, round));
+        if let Some(header) = self.validators[proposer_index].equivocating_proposal(&block, round) {
+            self.evidence.observe_proposal(&header);
+        }
+
+        let powers: Vec<f64> = (0..self.validators.len())
+            .map(|i| self.voting_power(i))
+            .collect();
+        let total_weight: f64 = powers.iter().sum();
+
+        let mut prevotes = VoteSet::new(VoteType::Prevote, height, round, total_weight);
+        let mut verdicts = Vec::with_capacity(self.validators.len());
+        // Why the block was rejected, as found by the first validator to prevote nil on it. A nil
+        // prevote on a block that validates does not make the proposal a violation.
+        let mut invalid = None;
+        for (i, (validator, power)) in self.validators.iter().zip(&powers).enumerate() {
+            if !validator.online {
+                continue;
+            }
+            let vote = validator.prevote(&block, round);
+            if vote.verify_signature(&self.registry).is_ok() {
+                let verdict = if vote.is_nil() {
+                    Verdict::Reject
+                } else {
+                    Verdict::Accept
+                };
+                outcome
+                    .verdicts
+                    .insert(validator.validator_id.clone(), verdict);
+                if vote.is_nil() && invalid.is_none() {
+                    invalid = validator.validate_block(&block).err();
+                }
+                if i != proposer_index {
+                    let timely = round_start_ms + validator.vote_delay_ms <= deadline_ms;
+                    verdicts.push((i, !vote.is_nil(), timely));
                 }
+                self.evidence.observe(&vote);
+                prevotes.add(vote, *power);
+            }
+            // A conflicting prevote reaches the pool but not the tally, which counts a validator's
+            // first vote only.
+            if let Some(vote) = validator.equivocating_prevote(&block, round) {
+                if vote.verify_signature(&self.registry).is_ok() {
+                    self.evidence.observe(&vote);
+                }
+            }
+        }
+        let polka = prevotes.quorum().flatten();
+
+        let mut precommits = VoteSet::new(VoteType::Precommit, height, round, total_weight);
+        for (validator, power) in self
+            .validators
+            .iter()
+            .zip(&powers)
+            .filter(|(v, _)| v.online)
+        {
+            let vote = validator.precommit(height, round, polka);
+            if vote.verify_signature(&self.registry).is_ok() {
+                precommits.add(vote, *power);
             }
         }
+        let certificate = precommits
+            .quorum()
+            .flatten()
+            .and_then(|hash| precommits.certificate(hash));
+
+        let is_finalized = certificate.is_some();
+        let rejected = prevotes.weight_for(None) * 3.0 > total_weight;
+        let (proposer_id, hash) = (
+            self.validators[proposer_index].validator_id.clone(),
+            block.hash(),
+        );
+        outcome.block_hash = Some(hash);
+        outcome.finalized = is_finalized;
+        // A finalized block is announced once it is committed, at the end of the round.
+        if !is_finalized {
+            self.events.emit(ConsensusEvent::BlockRejected {
+                height,
+                round,
+                proposer: proposer_id.clone(),
+                hash,
+            });
+        }
 
         let proposer = &mut self.validators[proposer_index];
-        let violation_occurred = !is_valid && is_malicious;
-        proposer.update_scores(is_valid, violation_occurred);
-        if is_valid {
+        let violation_occurred = !is_finalized && rejected && invalid.is_some();
+        proposer.update_scores(is_finalized, violation_occurred);
+        if is_finalized {
             proposer.increment_accepted_blocks();
         }
         proposer.increment_proposed_blocks();
+        if violation_occurred {
+            self.slash_validator(&proposer_id, SlashReason::InvalidBlock);
+        }
+
+        for (i, endorsed, timely) in verdicts {
+            let validator = &mut self.validators[i];
+            validator.record_validation(endorsed == is_finalized, timely);
+            validator.settle_validation();
+        }
+
+        for evidence in self.evidence.take_pending() {
+            // Evidence found by the network itself always verifies; a duplicate is simply dropped.
+            let _ = self.submit_evidence(evidence);
+        }
+
+        // Every validator built on the same head and the block gathered a quorum, so committing it
+        // cannot fail short of a bug; failing halfway would leave validators on different chains.
+        match certificate {
+            Some(certificate) => {
+                let weight = precommits.weight_for(Some(hash));
+                let inserted = self.tree.insert(block, weight).map_err(|e| {
+                    ConsensusError::InvariantViolated(format!(
+                        "certified block {} does not extend the block tree: {:?}",
+                        hash, e
+                    ))
+                })?;
+                if let Some(event) = inserted {
+                    self.apply_reorg(event)?;
+                }
+                let finalized = self.tree.finalize(hash).map_err(|e| {
+                    ConsensusError::InvariantViolated(format!(
+                        "certified block {} cannot be finalized: {:?}",
+                        hash, e
+                    ))
+                })?;
+                if let Some(event) = finalized {
+                    self.apply_reorg(event)?;
+                }
+                self.certificates.insert(height, certificate);
+                self.events.emit(ConsensusEvent::BlockFinalized {
+                    height,
+                    round,
+                    proposer: proposer_id,
+                    hash,
+                });
+                let horizon = self.evidence_horizon();
+                self.evidence.prune_below(horizon);
+                self.punished.retain(|(_, _, h, _)| *h >= horizon);
+                self.round = 0;
+                if height % self.epoch_length == 0 {
+                    self.transition_epoch();
+                }
+            }
+            None => self.round += 1,
+        }
+        Ok(outcome)
+    }
+
+    /// Moves the state that follows the canonical head onto the new head.
+    ///
+    /// A block is finalized as soon as it is certified, so the head only ever moves forward: each
+    /// applied block is committed by every validator, applied to the balances, delegations and
+    /// mempool and pays its proposer the block reward.
+    ///
+    /// Fails with [`ConsensusError::InvariantViolated`] if the event reverts a block or a
+    /// validator cannot commit an applied one.
+    fn apply_reorg(&mut self, event: ReorgEvent) -> Result<(), ConsensusError> {
+        if let Some(block) = event.reverted.first() {
+            return Err(ConsensusError::InvariantViolated(format!(
+                "finalized block {} was reverted",
+                block.hash()
+            )));
+        }
+        for block in event.applied {
+            for validator in &mut self.validators {
+                validator.commit_block(&block).map_err(|e| {
+                    ConsensusError::InvariantViolated(format!(
+                        "{} cannot commit block {} on the network head: {:?}",
+                        validator.validator_id,
+                        block.hash(),
+                        e
+                    ))
+                })?;
+            }
+            self.accounts.apply_block(&block);
+            self.mempool.remove_included(&block.transactions);
+            self.mempool.remove_stale(&self.accounts);
+            let reward = self.issuance.block_reward_at(block.header.height);
+            self.mint(&block.header.proposer, reward);
+            self.chain.push(block);
+        }
+        self.sync_delegations();
+        Ok(())
+    }
+
+    /// Ends a round whose proposer is offline: once the propose timeout expires, every online
+    /// validator prevotes nil to skip the proposer. The network moves on to the next round only if
+    /// those skip votes carry more than two thirds of the voting power, and the skipped proposer
+    /// is then slashed for downtime; otherwise the round is played again.
+    fn skip_round(
+        &mut self,
+        proposer_index: usize,
+        height: u64,
+        round: u32,
+        verdicts: &mut BTreeMap<String, Verdict>,
+    ) {
+        self.clock_ms += self.timeouts.propose_timeout(round);
+        let powers: Vec<f64> = (0..self.validators.len())
+            .map(|i| self.voting_power(i))
+            .collect();
+        let total_weight: f64 = powers.iter().sum();
+        let mut skips = VoteSet::new(VoteType::Prevote, height, round, total_weight);
+        for (validator, power) in self
+            .validators
+            .iter()
+            .zip(&powers)
+            .filter(|(v, _)| v.online)
+        {
+            let vote = validator.skip_prevote(height, round);
+            if vote.verify_signature(&self.registry).is_ok() {
+                verdicts.insert(validator.validator_id.clone(), Verdict::Reject);
+                self.evidence.observe(&vote);
+                skips.add(vote, *power);
+            }
+        }
+        if skips.quorum() == Some(None) {
+            let proposer_id = self.validators[proposer_index].validator_id.clone();
+            self.slash_validator(&proposer_id, SlashReason::Downtime);
+            self.round += 1;
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Weight of a validator in proposer selection, as defined by its scoring policy from its synergy
+/// score and its own and delegated stake.
+///
+/// Penalties can drive a score far below zero; such validators get no weight rather than a
+/// negative one, which would shrink everyone else's odds.
+fn selection_weight(validator: &PosygDcsEngine) -> f64 {
+    validator
+        .scoring
+        .selection_weight(validator.get_synergy_score(), validator.total_stake())
+        .max(0.0)
+}
@@ -1,32 +1,487 @@
 // src/main.rs
 
-use peo_consensus::{ConsensusEngine, Network, PosygDcsEngine};
+use peo_consensus::analysis::MonteCarlo;
+use peo_consensus::crypto::{decode_key, SigningKey};
+use peo_consensus::events::ConsensusEvent;
+use peo_consensus::genesis::Genesis;
+use peo_consensus::gossip::{Gossip, GossipConfig, Received};
+use peo_consensus::metrics::{Metrics, MetricsServer};
+use peo_consensus::net::{Credentials, TcpTransport, Transport};
+use peo_consensus::node::Message;
+use peo_consensus::rpc::RpcServer;
+use peo_consensus::vote::has_supermajority;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-// Main function to simulate the consensus mechanism over 5 rounds.
+/// Genesis of the local simulation when no `--genesis` is given.
+const DEFAULT_GENESIS: &str = include_str!("../config/genesis.toml");
+
+/// How often a validator asks its peers for the blocks it may have missed.
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+// Without `--id`, simulates the whole validator set of a genesis file in one process, for 5
+// rounds unless told otherwise; a seed makes the run reproducible:
+//
+//     consensus_node [SEED] [--genesis config/genesis.toml] [--rounds 5]
+//
+// With `--id`, runs a single validator of a network that talks to its peers over TCP. The
+// validator set comes from a genesis file that lists every validator's public key, and the
+// validator's secret key must be given, in hex, directly or in a file:
+//
+//     consensus_node --id v1 --genesis config/devnet.toml \
+//         (--key <HEX SECRET KEY> | --key-file config/devnet-keys/v1.key) \
+//         --listen 0.0.0.0:30303 --peers node2:30303,node3:30303 [--rpc 0.0.0.0:8545] \
+//         [--metrics 0.0.0.0:9100] [--blocks 10]
+//
+// `--rpc` serves the chain head, blocks, validators and round state over HTTP and JSON-RPC, and
+// `--metrics` serves Prometheus metrics at `/metrics`.
+//
+// `analyze` runs many seeded simulations of the validator set of a genesis file, the bundled one
+// by default, and reports how fairly proposals were spread, as CSV or JSON:
+//
+//     consensus_node analyze --genesis config/genesis.toml --runs 100 --rounds 1000 \
+//         [--seed 0] [--format csv|json] [--output report.csv] [--trajectories scores.csv]
 fn main() {
-    let mut network = Network {
-        validators: vec![
-            PosygDcsEngine::new("validator1".to_string(), 1000, false),
-            PosygDcsEngine::new("validator2".to_string(), 1500, false),
-            PosygDcsEngine::new("validator3".to_string(), 800, true),
-        ],
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "analyze") {
+        analyze(&args[1..]);
+    } else if args.iter().any(|arg| arg == "--id") {
+        run_node(&args);
+    } else {
+        simulate(&args);
+    }
+}
+
+fn load_genesis(path: &str) -> Genesis {
+    Genesis::load(path).unwrap_or_else(|e| {
+        eprintln!("Invalid genesis {}: {:?}", path, e);
+        std::process::exit(2);
+    })
+}
+
+fn simulate(args: &[String]) {
+    let usage = |message: &str| -> ! {
+        eprintln!("{}", message);
+        eprintln!("Usage: consensus_node [SEED] [--genesis <FILE>] [--rounds <N>]");
+        std::process::exit(2);
     };
+    let mut seed = None;
+    let mut genesis = None;
+    let mut rounds = 5;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            let invalid = || {
+                usage(&format!(
+                    "Invalid seed '{}': expected an unsigned integer",
+                    arg
+                ))
+            };
+            seed = Some(arg.parse::<u64>().unwrap_or_else(|_| invalid()));
+            continue;
+        }
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("Missing value for {}", arg)));
+        match arg.as_str() {
+            "--genesis" => genesis = Some(load_genesis(value)),
+            "--rounds" => {
+                rounds = value
+                    .parse()
+                    .unwrap_or_else(|_| usage(&format!("Invalid round count '{}'", value)))
+            }
+            _ => usage(&format!("Unknown option {}", arg)),
+        }
+    }
+    let genesis = genesis.unwrap_or_else(|| {
+        Genesis::from_toml_str(DEFAULT_GENESIS).expect("the default genesis is valid")
+    });
+    println!(
+        "Simulating chain {} with {} validators",
+        genesis.chain_id,
+        genesis.validators.len()
+    );
+    if let Some(seed) = seed {
+        println!("Using seed {}", seed);
+    }
+    let mut network = genesis
+        .network(seed)
+        .unwrap_or_else(|e| usage(&format!("{:?}", e)));
 
-    for round in 1..=5 {
+    network.subscribe(Arc::new(log_event));
+
+    for round in 1..=rounds {
         println!("Starting consensus round {}", round);
-        network.run_consensus_round();
+        if let Err(e) = network.run_consensus_round() {
+            eprintln!("Round {} failed: {:?}", round, e);
+            std::process::exit(1);
+        }
+        println!();
+    }
+}
+
+fn log_event(event: &ConsensusEvent) {
+    match event {
+        ConsensusEvent::ProposerSelected {
+            height,
+            round,
+            proposer,
+            online: true,
+        } => {
+            println!(
+                "{} selected to propose height {} in round {}",
+                proposer, height, round
+            )
+        }
+        ConsensusEvent::ProposerSelected {
+            height,
+            round,
+            proposer,
+            online: false,
+        } => {
+            println!(
+                "{} is offline, skipping round {} at height {}",
+                proposer, round, height
+            )
+        }
+        ConsensusEvent::BlockProposed {
+            proposer,
+            hash,
+            transactions,
+            ..
+        } => {
+            println!(
+                "{} proposed block {} with {} transactions",
+                proposer, hash, transactions
+            )
+        }
+        ConsensusEvent::BlockRejected { height, hash, .. } => {
+            println!("Block {} rejected at height {}", hash, height)
+        }
+        ConsensusEvent::BlockFinalized { height, hash, .. } => {
+            println!("Block {} finalized at height {}", hash, height)
+        }
+        ConsensusEvent::ScoreUpdated {
+            validator,
+            previous,
+            score,
+        } => {
+            println!(
+                "{}: Synergy Score {:.2} -> {:.2}",
+                validator, previous, score
+            )
+        }
+        ConsensusEvent::ViolationRecorded {
+            validator,
+            reason,
+            slashed,
+            ..
+        } => {
+            println!("{} slashed {} for {:?}", validator, slashed, reason)
+        }
+    }
+}
+
+fn analyze(args: &[String]) {
+    let usage = |message: &str| -> ! {
+        eprintln!("{}", message);
+        eprintln!(
+            "Usage: consensus_node analyze [--genesis <FILE>] [--runs <N>] [--rounds <M>] \
+             [--seed <SEED>] [--format csv|json] [--output <FILE>] [--trajectories <FILE>]"
+        );
+        std::process::exit(2);
+    };
+    let mut genesis = None;
+    let mut monte_carlo = MonteCarlo {
+        runs: 100,
+        rounds: 1000,
+        seed: 0,
+    };
+    let mut format = "csv".to_string();
+    let mut output = None;
+    let mut trajectories = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("Missing value for {}", flag)));
+        let number = || {
+            value
+                .parse::<u64>()
+                .unwrap_or_else(|_| usage(&format!("Invalid number '{}'", value)))
+        };
+        match flag.as_str() {
+            "--genesis" => genesis = Some(load_genesis(value)),
+            "--runs" => monte_carlo.runs = number(),
+            "--rounds" => monte_carlo.rounds = number(),
+            "--seed" => monte_carlo.seed = number(),
+            "--format" if value == "csv" || value == "json" => format = value.clone(),
+            "--format" => usage(&format!("Unknown format '{}'", value)),
+            "--output" => output = Some(value.clone()),
+            "--trajectories" => trajectories = Some(value.clone()),
+            _ => usage(&format!("Unknown option {}", flag)),
+        }
+    }
+    let genesis = genesis.unwrap_or_else(|| {
+        Genesis::from_toml_str(DEFAULT_GENESIS).expect("the default genesis is valid")
+    });
+    let report = monte_carlo
+        .run(&genesis)
+        .unwrap_or_else(|e| usage(&format!("{:?}", e)));
+    eprintln!(
+        "{} runs of {} rounds: Gini coefficient {:.4}, Nakamoto coefficient {}",
+        report.runs, report.rounds, report.gini, report.nakamoto
+    );
+
+    let write = |path: &str, contents: String| {
+        std::fs::write(path, contents).unwrap_or_else(|e| {
+            eprintln!("Cannot write {}: {}", path, e);
+            std::process::exit(1);
+        })
+    };
+    let contents = if format == "json" {
+        report.to_json()
+    } else {
+        report.to_csv()
+    };
+    match output {
+        Some(path) => write(&path, contents),
+        None => print!("{}", contents),
+    }
+    if let Some(path) = trajectories {
+        write(&path, report.trajectories_csv());
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "Usage: consensus_node --id <ID> --genesis <FILE> (--key <HEX> | --key-file <FILE>) \
+         [--listen <ADDR>] [--peers <ADDR,...>] [--rpc <ADDR>] [--metrics <ADDR>] [--blocks <N>]"
+    );
+    std::process::exit(2);
+}
+
+fn run_node(args: &[String]) {
+    let mut id = None;
+    let mut listen = "0.0.0.0:30303".to_string();
+    let mut peers = Vec::new();
+    let mut genesis = None;
+    let mut key = None;
+    let mut rpc = None;
+    let mut metrics_addr = None;
+    let mut blocks = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage_error(&format!("Missing value for {}", flag)));
+        match flag.as_str() {
+            "--id" => id = Some(value.clone()),
+            "--listen" => listen = value.clone(),
+            "--peers" => {
+                peers = value
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "--genesis" => genesis = Some(load_genesis(value)),
+            "--key" => {
+                let bytes = decode_key(value)
+                    .unwrap_or_else(|| usage_error("Invalid --key: expected 64 hex characters"));
+                key = Some(SigningKey::from_bytes(&bytes));
+            }
+            "--key-file" => {
+                let contents = std::fs::read_to_string(value)
+                    .unwrap_or_else(|e| usage_error(&format!("Cannot read {}: {}", value, e)));
+                let bytes = decode_key(contents.trim()).unwrap_or_else(|| {
+                    usage_error(&format!(
+                        "Invalid key in {}: expected 64 hex characters",
+                        value
+                    ))
+                });
+                key = Some(SigningKey::from_bytes(&bytes));
+            }
+            "--rpc" => rpc = Some(value.clone()),
+            "--metrics" => metrics_addr = Some(value.clone()),
+            "--blocks" => {
+                blocks =
+                    Some(value.parse::<u64>().unwrap_or_else(|_| {
+                        usage_error(&format!("Invalid block count '{}'", value))
+                    }))
+            }
+            _ => usage_error(&format!("Unknown option {}", flag)),
+        }
+    }
+    let id = id.unwrap_or_else(|| usage_error("Missing --id"));
+    let genesis = genesis.unwrap_or_else(|| usage_error("Missing --genesis"));
+    let key = key.unwrap_or_else(|| usage_error("Missing --key or --key-file"));
+    let mut node = genesis
+        .node(&id, key.clone())
+        .unwrap_or_else(|e| usage_error(&format!("{:?}", e)));
+    node.subscribe(Arc::new(log_event));
+    let credentials = Credentials {
+        chain_id: genesis.chain_id.clone(),
+        validator_id: id.clone(),
+        signing_key: key,
+        registry: genesis
+            .registry()
+            .unwrap_or_else(|e| usage_error(&format!("{:?}", e))),
+    };
+    println!("Validator {} joining chain {}", id, genesis.chain_id);
+    let transport = TcpTransport::bind(credentials, &listen).unwrap_or_else(|e| {
+        eprintln!("Cannot listen on {}: {:?}", listen, e);
+        std::process::exit(1);
+    });
+    println!("Validator {} listening on {}", id, transport.local_addr());
+    for peer in &peers {
+        transport.connect(peer.clone());
+    }
+    // Consensus needs more than two thirds of the stake, so the node starts once it is connected to
+    // enough peers to make that up with its own; peers that are down join when they come back and
+    // catch up by sync.
+    let stake = |id: &str| genesis.validator(id).map_or(0, |v| v.stake);
+    let total_stake: u64 = genesis.validators.iter().map(|v| v.stake).sum();
+    loop {
+        let connected = transport.peers();
+        let reachable = stake(&id) + connected.iter().map(|peer| stake(peer)).sum::<u64>();
+        if has_supermajority(reachable as f64, total_stake as f64) {
+            break;
+        }
+        if !transport.wait_for_peers(connected.len() + 1, Duration::from_secs(5)) {
+            println!("Waiting for peers, connected to {:?}", connected);
+        }
+    }
 
-        for (i, validator) in network.validators.iter().enumerate() {
+    // Messages spread by gossip: each node relays what it has not seen yet to a few peers.
+    let mut gossip = Gossip::new(GossipConfig::default(), rand::random());
+    let started = Instant::now();
+    let now = || started.elapsed().as_millis() as u64;
+    let publish = |gossip: &mut Gossip, messages: Vec<Message>| {
+        for message in messages {
+            for peer in gossip.publish(&message, &transport.peers(), now()) {
+                transport.send(&peer, &message);
+            }
+        }
+    };
+
+    // Rejected proposals are counted as the node reports them.
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+    let observed = Arc::clone(&metrics);
+    node.subscribe(Arc::new(move |event: &ConsensusEvent| {
+        observed
+            .lock()
+            .expect("metrics lock poisoned")
+            .record_event(event)
+    }));
+
+    // The API reads the node between messages.
+    let shared = Arc::new(Mutex::new(node));
+    if let Some(addr) = rpc {
+        let server = RpcServer::serve(&addr, Arc::clone(&shared)).unwrap_or_else(|e| {
+            eprintln!("Cannot serve the API on {}: {:?}", addr, e);
+            std::process::exit(1);
+        });
+        println!("Serving the API on http://{}", server.local_addr());
+    }
+    if let Some(addr) = metrics_addr {
+        let server = MetricsServer::serve(&addr, Arc::clone(&metrics)).unwrap_or_else(|e| {
+            eprintln!("Cannot serve metrics on {}: {:?}", addr, e);
+            std::process::exit(1);
+        });
+        println!("Serving metrics on http://{}/metrics", server.local_addr());
+    }
+
+    let started_messages = shared.lock().expect("node lock poisoned").start();
+    publish(&mut gossip, started_messages);
+    // A round that makes no progress before its timeout is skipped, and so is one whose
+    // precommits disagree once its precommit timeout has passed.
+    let mut step = (1, 0, false);
+    let mut round_started = Instant::now();
+    let mut round_entered = Instant::now();
+    // Every peer is regularly asked for the blocks this node missed while down or cut off; sync
+    // messages go to one peer each rather than through gossip.
+    let mut synced = Instant::now();
+    loop {
+        // Peers are written to outside the node lock, so a slow peer never stalls the API.
+        let mut outgoing = Vec::new();
+        let mut replies = Vec::new();
+        let mut received = None;
+        let mut sync_request = None;
+        match transport.recv_timeout(Duration::from_millis(100)) {
+            Some((from, Message::SyncRequest { height })) => sync_request = Some((from, height)),
+            Some((_, message @ Message::SyncResponse { .. })) => received = Some(message),
+            Some((from, message)) => {
+                match gossip.receive(&from, &message, &transport.peers(), now()) {
+                    Received::Fresh(targets) => {
+                        for peer in targets {
+                            transport.send(&peer, &message);
+                        }
+                        received = Some(message);
+                    }
+                    Received::Resent => received = Some(message),
+                    Received::Duplicate | Received::RateLimited => {}
+                }
+            }
+            None => {}
+        }
+        let mut node = shared.lock().expect("node lock poisoned");
+        if let Some(message) = received {
+            outgoing.extend(node.handle(message));
+        }
+        if let Some((from, height)) = sync_request {
+            replies.extend(node.sync_response(height).map(|response| (from, response)));
+        }
+        if synced.elapsed() >= SYNC_INTERVAL {
+            let request = node.sync_request();
+            replies.extend(
+                transport
+                    .peers()
+                    .into_iter()
+                    .map(|peer| (peer, request.clone())),
+            );
+            synced = Instant::now();
+        }
+        // The node reports its events to the metrics, so it is never driven under their lock.
+        let lock_metrics = || metrics.lock().expect("metrics lock poisoned");
+        let position = (node.height(), node.round(), node.awaiting_precommits());
+        if position != step {
+            if (position.0, position.1) != (step.0, step.1) {
+                lock_metrics().record_round(round_entered.elapsed().as_secs_f64());
+                round_entered = Instant::now();
+            }
+            step = position;
+            round_started = Instant::now();
+        } else if round_started.elapsed() >= Duration::from_millis(node.round_timeout_ms()) {
             println!(
-                "Validator {} ({}): Synergy Score = {:.2}, Violations = {}, Proposed = {}, Accepted = {}",
-                i + 1,
-                validator.validator_id(),
-                validator.get_synergy_score(),
-                validator.violations(),
-                validator.proposed_blocks(),
-                validator.accepted_blocks()
+                "Round {} at height {} timed out",
+                node.round(),
+                node.height()
             );
+            lock_metrics().record_timeout();
+            outgoing.extend(node.timeout());
+            round_started = Instant::now();
+        }
+        if let Some(error) = node.fault() {
+            eprintln!("Node halted: {:?}", error);
+            std::process::exit(1);
+        }
+        let mut metrics = lock_metrics();
+        metrics.update(&*node);
+        metrics.set_peers(transport.peers().len());
+        drop(metrics);
+        let done = blocks.is_some_and(|blocks| node.head().header.height >= blocks);
+        drop(node);
+        for (peer, message) in replies {
+            transport.send(&peer, &message);
+        }
+        publish(&mut gossip, outgoing);
+        if done {
+            break;
         }
-        println!();
     }
-}
\ No newline at end of file
+}
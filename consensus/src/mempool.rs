@@ -0,0 +1,250 @@
+// src/mempool.rs
+
+use crate::block::Hash;
+use crate::state::AccountState;
+use crate::transaction::Transaction;
+use crate::ConsensusError;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+
+/// Limits of a [`Mempool`] and of the blocks built from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MempoolConfig {
+    /// Most transactions held at once.
+    pub max_transactions: usize,
+    /// Most bytes of transactions held at once.
+    pub max_bytes: usize,
+    /// Largest single transaction accepted, in bytes.
+    pub max_transaction_bytes: usize,
+    /// Most bytes of transactions a proposer puts in one block.
+    pub max_block_bytes: usize,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            max_transactions: 10_000,
+            max_bytes: 4 * 1024 * 1024,
+            max_transaction_bytes: 64 * 1024,
+            max_block_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// A transaction waiting to be included in a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransaction {
+    /// The transaction.
+    pub transaction: Transaction,
+    /// Hash identifying the transaction.
+    pub hash: Hash,
+    /// Size of the transaction's encoding, in bytes.
+    pub size: usize,
+}
+
+/// Transactions submitted to the network and not yet finalized.
+///
+/// Transactions are deduplicated by hash and ordered by fee, highest first, then by arrival. When
+/// the pool is full, a new transaction evicts the lowest-priority ones if it pays a higher fee
+/// than they do, and is rejected otherwise. Only stateless checks are made on submission; blocks
+/// are built from the transactions that pass the stateful ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::SigningKey;
+/// use peo_consensus::mempool::{Mempool, MempoolConfig};
+/// use peo_consensus::state::AccountState;
+/// use peo_consensus::transaction::{Transaction, TransactionKind};
+///
+/// let key = SigningKey::from_bytes(&[1; 32]);
+/// let mut state = AccountState::new();
+/// state.open("alice".to_string(), key.verifying_key(), 100);
+/// let transfer = |nonce, fee| {
+///     let kind = TransactionKind::Transfer { to: "bob".to_string(), amount: 1 };
+///     let mut tx = Transaction::new("alice".to_string(), nonce, kind, fee);
+///     tx.sign(&key);
+///     tx
+/// };
+///
+/// let config = MempoolConfig { max_transactions: 2, ..MempoolConfig::default() };
+/// let mut mempool = Mempool::new(config);
+/// mempool.submit(transfer(0, 5)).unwrap();
+/// mempool.submit(transfer(1, 9)).unwrap();
+/// assert!(mempool.submit(transfer(0, 5)).is_err());
+///
+/// // A full pool makes room for a better-paying transaction only.
+/// assert!(mempool.submit(transfer(2, 1)).is_err());
+/// mempool.submit(transfer(2, 7)).unwrap();
+/// assert!(mempool.get(&transfer(0, 5).hash()).is_none());
+///
+/// // Nonce 0 was evicted, so nothing applies yet.
+/// assert!(mempool.select(&state, "v1").is_empty());
+/// mempool.submit(transfer(0, 8)).unwrap(); // Evicts nonce 2.
+/// let nonces: Vec<u64> = mempool.select(&state, "v1").iter().map(|tx| tx.nonce).collect();
+/// assert_eq!(nonces, vec![0, 1]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Mempool {
+    config: MempoolConfig,
+    by_hash: HashMap<Hash, (PendingTransaction, u64)>,
+    // Highest fee first, then earliest arrival.
+    order: BTreeSet<(Reverse<u64>, u64, Hash)>,
+    bytes: usize,
+    next_arrival: u64,
+}
+
+impl Mempool {
+    /// Creates an empty mempool with the given limits.
+    pub fn new(config: MempoolConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the mempool's limits.
+    pub fn config(&self) -> &MempoolConfig {
+        &self.config
+    }
+
+    /// Returns the number of pending transactions.
+    pub fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    /// Returns `true` if no transaction is pending.
+    pub fn is_empty(&self) -> bool {
+        self.by_hash.is_empty()
+    }
+
+    /// Returns the total size of the pending transactions, in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns `true` if the transaction with the given hash is pending.
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.by_hash.contains_key(hash)
+    }
+
+    /// Returns the pending transaction with the given hash.
+    pub fn get(&self, hash: &Hash) -> Option<&PendingTransaction> {
+        self.by_hash.get(hash).map(|(pending, _)| pending)
+    }
+
+    /// Returns the pending transactions in priority order.
+    pub fn iter(&self) -> impl Iterator<Item = &PendingTransaction> {
+        self.order.iter().map(|(_, _, hash)| &self.by_hash[hash].0)
+    }
+
+    /// Checks `transaction` without regard to account state and adds it to the pool, returning
+    /// its hash.
+    ///
+    /// Transactions failing [`Transaction::check_stateless`] are rejected with its error,
+    /// oversized ones with `InvalidTransaction`, transactions already pending with
+    /// `DuplicateTransaction`, and transactions that cannot outbid enough pending ones to fit
+    /// with `MempoolFull`.
+    pub fn submit(&mut self, transaction: Transaction) -> Result<Hash, ConsensusError> {
+        transaction.check_stateless()?;
+        let size = transaction.encode().len();
+        if size > self.config.max_transaction_bytes {
+            return Err(ConsensusError::InvalidTransaction);
+        }
+        let fee = transaction.fee;
+        let hash = transaction.hash();
+        if self.by_hash.contains_key(&hash) {
+            return Err(ConsensusError::DuplicateTransaction);
+        }
+
+        // Find the lowest-priority transactions that would have to go, without evicting yet.
+        let mut evicted = Vec::new();
+        let (mut count, mut bytes) = (self.len(), self.bytes);
+        let mut lowest = self.order.iter().rev();
+        while count >= self.config.max_transactions || bytes + size > self.config.max_bytes {
+            match lowest.next() {
+                Some(&(Reverse(lowest_fee), _, victim)) if lowest_fee < fee => {
+                    count -= 1;
+                    bytes -= self.by_hash[&victim].0.size;
+                    evicted.push(victim);
+                }
+                _ => return Err(ConsensusError::MempoolFull),
+            }
+        }
+        for victim in evicted {
+            self.remove(&victim);
+        }
+
+        let arrival = self.next_arrival;
+        self.next_arrival += 1;
+        self.order.insert((Reverse(fee), arrival, hash));
+        self.bytes += size;
+        self.by_hash.insert(
+            hash,
+            (
+                PendingTransaction {
+                    transaction,
+                    hash,
+                    size,
+                },
+                arrival,
+            ),
+        );
+        Ok(hash)
+    }
+
+    /// Removes the transaction with the given hash, returning it if it was pending.
+    pub fn remove(&mut self, hash: &Hash) -> Option<PendingTransaction> {
+        let (pending, arrival) = self.by_hash.remove(hash)?;
+        self.order
+            .remove(&(Reverse(pending.transaction.fee), arrival, *hash));
+        self.bytes -= pending.size;
+        Some(pending)
+    }
+
+    /// Removes the transactions of a finalized block.
+    pub fn remove_included(&mut self, transactions: &[Transaction]) {
+        for transaction in transactions {
+            self.remove(&transaction.hash());
+        }
+    }
+
+    /// Removes the transactions whose nonce has already been used in `state`, which can never be
+    /// applied.
+    pub fn remove_stale(&mut self, state: &AccountState) {
+        let stale: Vec<Hash> = self
+            .iter()
+            .filter(|pending| pending.transaction.nonce < state.nonce(&pending.transaction.sender))
+            .map(|pending| pending.hash)
+            .collect();
+        for hash in stale {
+            self.remove(&hash);
+        }
+    }
+
+    /// Picks the transactions for the next block `proposer` builds on top of `state`: the
+    /// highest-priority ones that apply in turn and fit in `max_block_bytes`. A transaction whose
+    /// nonce is not due yet is picked as soon as the earlier ones from its sender are. Transactions
+    /// stay pending until [`remove_included`](Self::remove_included) is called for the finalized
+    /// block.
+    pub fn select(&self, state: &AccountState, proposer: &str) -> Vec<Transaction> {
+        let mut state = state.clone();
+        let mut room = self.config.max_block_bytes;
+        let mut selected = Vec::new();
+        let mut waiting: Vec<&PendingTransaction> = self.iter().collect();
+        loop {
+            let before = selected.len();
+            waiting.retain(|pending| {
+                if pending.size > room || state.apply(&pending.transaction, proposer).is_err() {
+                    return true;
+                }
+                room -= pending.size;
+                selected.push(pending.transaction.clone());
+                false
+            });
+            if selected.len() == before {
+                return selected;
+            }
+        }
+    }
+}
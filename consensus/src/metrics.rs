@@ -0,0 +1,369 @@
+// src/metrics.rs
+
+use crate::events::ConsensusEvent;
+use crate::rpc::{serve_http, ChainView, ValidatorStatus};
+use crate::ConsensusError;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+/// Content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Default upper bounds of the round duration buckets, in seconds.
+pub const DEFAULT_ROUND_BUCKETS: [f64; 10] =
+    [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// A histogram of observed values with cumulative buckets, as Prometheus exposes them.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::metrics::Histogram;
+///
+/// let mut histogram = Histogram::new(&[1.0, 5.0]);
+/// histogram.observe(0.5);
+/// histogram.observe(3.0);
+/// histogram.observe(7.0);
+/// assert_eq!(histogram.cumulative_counts(), vec![1, 2]);
+/// assert_eq!(histogram.count(), 3);
+/// assert_eq!(histogram.sum(), 10.5);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    /// Creates an empty histogram with buckets bounded by `bounds`, in increasing order; values
+    /// above the last bound only count toward the `+Inf` bucket.
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    /// Records `value`.
+    pub fn observe(&mut self, value: f64) {
+        if let Some(bucket) = self.bounds.iter().position(|&bound| value <= bound) {
+            self.counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Returns the number of values at most each bound.
+    pub fn cumulative_counts(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0, |total, count| {
+                *total += count;
+                Some(*total)
+            })
+            .collect()
+    }
+
+    /// Returns the number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of the recorded values.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+}
+
+/// Metrics of a running validator, rendered in the Prometheus text exposition format.
+///
+/// Round events are recorded as they happen, including the [`ConsensusEvent`]s passed to
+/// [`Metrics::record_event`] by an observer; the chain height, validator scores and proposal
+/// counts are read from a [`ChainView`] with [`Metrics::update`] before each scrape.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::metrics::Metrics;
+/// use peo_consensus::{Network, PosygDcsEngine};
+///
+/// use std::sync::{Arc, Mutex};
+///
+/// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+/// let mut network = Network::from_seed(vec![validator], 1);
+/// let metrics = Arc::new(Mutex::new(Metrics::new()));
+/// let observed = Arc::clone(&metrics);
+/// network.subscribe(Arc::new(move |event: &_| observed.lock().unwrap().record_event(event)));
+/// network.run_consensus_round().unwrap();
+/// let mut metrics = metrics.lock().unwrap();
+/// metrics.record_round(0.2);
+/// metrics.update(&network);
+/// metrics.set_peers(0);
+///
+/// let text = metrics.render();
+/// assert!(text.contains("peochain_rounds_total 1\n"));
+/// assert!(text.contains("peochain_finalized_height 1\n"));
+/// assert!(text.contains("peochain_blocks_accepted_total{validator=\"v1\"} 1\n"));
+/// assert!(text.contains("peochain_blocks_rejected_total{validator=\"v1\"} 0\n"));
+/// assert!(text.contains("peochain_round_duration_seconds_bucket{le=\"0.25\"} 1\n"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+    rounds: u64,
+    timeouts: u64,
+    round_durations: Histogram,
+    finalized_height: u64,
+    height: u64,
+    round: u32,
+    peers: usize,
+    validators: BTreeMap<String, ValidatorStatus>,
+    rejected: BTreeMap<String, u64>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Creates metrics with no recorded rounds and the default round duration buckets.
+    pub fn new() -> Self {
+        Self::with_round_buckets(&DEFAULT_ROUND_BUCKETS)
+    }
+
+    /// Creates metrics whose round duration histogram uses buckets bounded by `bounds` seconds.
+    pub fn with_round_buckets(bounds: &[f64]) -> Self {
+        Self {
+            rounds: 0,
+            timeouts: 0,
+            round_durations: Histogram::new(bounds),
+            finalized_height: 0,
+            height: 0,
+            round: 0,
+            peers: 0,
+            validators: BTreeMap::new(),
+            rejected: BTreeMap::new(),
+        }
+    }
+
+    /// Records a round that ended after `seconds`, whether it finalized a block or was skipped.
+    pub fn record_round(&mut self, seconds: f64) {
+        self.rounds += 1;
+        self.round_durations.observe(seconds);
+    }
+
+    /// Records a round that timed out.
+    pub fn record_timeout(&mut self) {
+        self.timeouts += 1;
+    }
+
+    /// Records a consensus event; every [`ConsensusEvent::BlockRejected`] counts against its
+    /// proposer.
+    pub fn record_event(&mut self, event: &ConsensusEvent) {
+        if let ConsensusEvent::BlockRejected { proposer, .. } = event {
+            *self.rejected.entry(proposer.clone()).or_default() += 1;
+        }
+    }
+
+    /// Sets the number of connected peers.
+    pub fn set_peers(&mut self, peers: usize) {
+        self.peers = peers;
+    }
+
+    /// Reads the finalized height, the round being decided and every validator's score, violations
+    /// and proposal counts from `view`.
+    pub fn update(&mut self, view: &impl ChainView) {
+        self.finalized_height = view.chain().last().map_or(0, |block| block.header.height);
+        let round = view.round_state();
+        self.height = round.height;
+        self.round = round.round;
+        for status in view.validator_statuses() {
+            self.validators.insert(status.id.clone(), status);
+        }
+    }
+
+    /// Returns the rounds recorded so far.
+    pub fn rounds(&self) -> u64 {
+        self.rounds
+    }
+
+    /// Returns the histogram of round durations, in seconds.
+    pub fn round_durations(&self) -> &Histogram {
+        &self.round_durations
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            writeln!(text, "# HELP {} {}", name, help).expect("writing to a string cannot fail");
+            writeln!(text, "# TYPE {} {}", name, kind).expect("writing to a string cannot fail");
+            for (labels, value) in samples {
+                writeln!(text, "{}{} {}", name, labels, value)
+                    .expect("writing to a string cannot fail");
+            }
+        };
+        let single = |value: String| vec![(String::new(), value)];
+        let per_validator = |value: fn(&ValidatorStatus) -> String| {
+            self.validators
+                .values()
+                .map(|v| (format!("{{validator=\"{}\"}}", escape(&v.id)), value(v)))
+                .collect::<Vec<_>>()
+        };
+
+        metric(
+            "peochain_rounds_total",
+            "counter",
+            "Consensus rounds run.",
+            single(self.rounds.to_string()),
+        );
+        metric(
+            "peochain_round_timeouts_total",
+            "counter",
+            "Consensus rounds that timed out.",
+            single(self.timeouts.to_string()),
+        );
+        metric(
+            "peochain_finalized_height",
+            "gauge",
+            "Height of the last finalized block.",
+            single(self.finalized_height.to_string()),
+        );
+        metric(
+            "peochain_height",
+            "gauge",
+            "Height being decided.",
+            single(self.height.to_string()),
+        );
+        metric(
+            "peochain_round",
+            "gauge",
+            "Round of the height being decided.",
+            single(self.round.to_string()),
+        );
+        metric(
+            "peochain_peers",
+            "gauge",
+            "Connected peers.",
+            single(self.peers.to_string()),
+        );
+        metric(
+            "peochain_validator_stake",
+            "gauge",
+            "Stake each validator votes with.",
+            per_validator(|v| v.stake.to_string()),
+        );
+        metric(
+            "peochain_synergy_score",
+            "gauge",
+            "Synergy score of each validator.",
+            per_validator(|v| v.synergy_score.to_string()),
+        );
+        metric(
+            "peochain_violations_total",
+            "counter",
+            "Invalid blocks proposed by each validator.",
+            per_validator(|v| v.violations.to_string()),
+        );
+        metric(
+            "peochain_blocks_proposed_total",
+            "counter",
+            "Rounds each validator was the proposer of.",
+            per_validator(|v| v.proposed.to_string()),
+        );
+        metric(
+            "peochain_blocks_accepted_total",
+            "counter",
+            "Blocks of each validator that were finalized.",
+            per_validator(|v| v.accepted.to_string()),
+        );
+        let rejected = self
+            .validators
+            .keys()
+            .chain(
+                self.rejected
+                    .keys()
+                    .filter(|id| !self.validators.contains_key(*id)),
+            )
+            .map(|id| {
+                let count = self.rejected.get(id).copied().unwrap_or(0);
+                (
+                    format!("{{validator=\"{}\"}}", escape(id)),
+                    count.to_string(),
+                )
+            })
+            .collect();
+        metric(
+            "peochain_blocks_rejected_total",
+            "counter",
+            "Blocks proposed by each validator that were rejected.",
+            rejected,
+        );
+
+        let histogram = &self.round_durations;
+        let mut buckets: Vec<(String, String)> = histogram
+            .bounds
+            .iter()
+            .zip(histogram.cumulative_counts())
+            .map(|(bound, count)| (format!("_bucket{{le=\"{}\"}}", bound), count.to_string()))
+            .collect();
+        buckets.push((
+            "_bucket{le=\"+Inf\"}".to_string(),
+            histogram.count.to_string(),
+        ));
+        buckets.push(("_sum".to_string(), histogram.sum.to_string()));
+        buckets.push(("_count".to_string(), histogram.count.to_string()));
+        metric(
+            "peochain_round_duration_seconds",
+            "histogram",
+            "Duration of consensus rounds.",
+            buckets,
+        );
+        text
+    }
+}
+
+/// Escapes a label value of the text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// An HTTP server exposing shared [`Metrics`] at `/metrics` for Prometheus to scrape.
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+}
+
+impl MetricsServer {
+    /// Listens on `addr` and serves `metrics` until the process exits.
+    pub fn serve(
+        addr: impl ToSocketAddrs,
+        metrics: Arc<Mutex<Metrics>>,
+    ) -> Result<Self, ConsensusError> {
+        let local_addr = serve_http(addr, move |request| match request {
+            Some(("GET", path, _)) if path.split('?').next() == Some("/metrics") => (
+                200,
+                CONTENT_TYPE,
+                metrics.lock().expect("metrics lock poisoned").render(),
+            ),
+            Some(("GET", _, _)) => (404, CONTENT_TYPE, "not found\n".to_string()),
+            Some(_) => (405, CONTENT_TYPE, "method not allowed\n".to_string()),
+            None => (400, CONTENT_TYPE, "malformed request\n".to_string()),
+        })?;
+        Ok(Self { local_addr })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
@@ -0,0 +1,639 @@
+// src/net.rs
+
+use crate::block::Hash;
+use crate::crypto::{SigningKey, ValidatorRegistry};
+use crate::node::Message;
+use crate::ConsensusError;
+use ed25519_dalek::Signer;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Version of the wire protocol; peers speaking another version are disconnected.
+pub const PROTOCOL_VERSION: u32 = 4;
+
+/// Largest frame a peer may send, in bytes.
+pub const MAX_FRAME_LEN: usize = 4 * 1024 * 1024;
+
+/// How long a peer has to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait before dialing a peer again after a failed or lost connection.
+const REDIAL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a write to a peer may block before the connection is given up.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many messages may wait to be written to a peer; further messages to it are dropped.
+const WRITE_QUEUE_LEN: usize = 1024;
+
+/// How many received messages may wait to be taken from the transport; peers' readers stop reading
+/// while it is full.
+const INBOX_LEN: usize = 4096;
+
+/// How many accepted connections may be handshaking at once; further ones are closed right away.
+pub const MAX_PENDING_HANDSHAKES: usize = 16;
+
+/// Domain separator for the signature a peer proves its identity with.
+const HANDSHAKE_DOMAIN: &[u8] = b"peochain/handshake";
+
+fn network_error(error: impl std::fmt::Display) -> ConsensusError {
+    ConsensusError::NetworkError(error.to_string())
+}
+
+/// Writes `message` as a frame: its JSON encoding prefixed by the encoding's length as a big-endian
+/// `u32`.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::block::Hash;
+/// use peo_consensus::net::{read_frame, write_frame};
+/// use peo_consensus::node::Message;
+///
+/// let message = Message::Hello {
+///     version: 1,
+///     chain_id: "peochain".to_string(),
+///     validator_id: "v1".to_string(),
+///     nonce: Hash::digest(b"nonce"),
+/// };
+/// let mut buffer = Vec::new();
+/// write_frame(&mut buffer, &message).unwrap();
+/// assert_eq!(read_frame(&mut buffer.as_slice()).unwrap(), message);
+/// ```
+pub fn write_frame(writer: &mut impl Write, message: &Message) -> Result<(), ConsensusError> {
+    let payload = serde_json::to_vec(message).map_err(network_error)?;
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(network_error(format!(
+            "frame of {} bytes exceeds the limit",
+            payload.len()
+        )));
+    }
+    writer
+        .write_all(&(payload.len() as u32).to_be_bytes())
+        .map_err(network_error)?;
+    writer.write_all(&payload).map_err(network_error)?;
+    writer.flush().map_err(network_error)
+}
+
+/// Reads one frame written by [`write_frame`].
+pub fn read_frame(reader: &mut impl Read) -> Result<Message, ConsensusError> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).map_err(network_error)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(network_error(format!(
+            "frame of {} bytes exceeds the limit",
+            len
+        )));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).map_err(network_error)?;
+    serde_json::from_slice(&payload).map_err(network_error)
+}
+
+/// What a validator proves about itself when connecting to peers, and the keys it checks their
+/// proofs against.
+#[derive(Clone)]
+pub struct Credentials {
+    /// Identifier of the chain the validator runs; peers on another chain are refused.
+    pub chain_id: String,
+    /// Identifier of the local validator.
+    pub validator_id: String,
+    /// Genesis key of the local validator, which signs its handshakes.
+    pub signing_key: SigningKey,
+    /// Genesis keys of the validators allowed to connect.
+    pub registry: ValidatorRegistry,
+}
+
+/// Returns the bytes `signer` signs to prove its identity to `peer` on chain `chain_id`, where
+/// each side sent the other its own random nonce.
+///
+/// The proof covers both identifiers and both nonces, so it only completes this one handshake
+/// between these two validators and cannot be replayed on another connection. Identifiers are
+/// length-prefixed so they cannot run into each other, and the chain identifier makes a proof
+/// given on one chain worthless on any other.
+fn handshake_bytes(
+    chain_id: &str,
+    signer: &str,
+    signer_nonce: &Hash,
+    peer: &str,
+    peer_nonce: &Hash,
+) -> Vec<u8> {
+    let mut bytes = HANDSHAKE_DOMAIN.to_vec();
+    for id in [chain_id, signer, peer] {
+        bytes.extend_from_slice(&(id.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(id.as_bytes());
+    }
+    bytes.extend_from_slice(signer_nonce.as_bytes());
+    bytes.extend_from_slice(peer_nonce.as_bytes());
+    bytes
+}
+
+/// Exchanges [`Message::Hello`] with the peer on `stream`, then proves the local validator's
+/// identity by signing both nonces and both identifiers and checks the peer's proof over the same,
+/// returning the peer's validator identifier.
+///
+/// Fails if the peer does not answer in time, sends anything else first, speaks another protocol
+/// version, runs another chain, is not in `credentials.registry` or signs the nonce with another
+/// key.
+pub fn handshake(
+    stream: &mut TcpStream,
+    credentials: &Credentials,
+) -> Result<String, ConsensusError> {
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(network_error)?;
+    stream
+        .set_write_timeout(Some(WRITE_TIMEOUT))
+        .map_err(network_error)?;
+    let nonce = Hash(rand::random());
+    write_frame(
+        stream,
+        &Message::Hello {
+            version: PROTOCOL_VERSION,
+            chain_id: credentials.chain_id.clone(),
+            validator_id: credentials.validator_id.clone(),
+            nonce,
+        },
+    )?;
+    let (chain_id, peer, peer_nonce) = match read_frame(stream)? {
+        Message::Hello {
+            version,
+            chain_id,
+            validator_id,
+            nonce,
+        } if version == PROTOCOL_VERSION => (chain_id, validator_id, nonce),
+        Message::Hello { version, .. } => {
+            return Err(network_error(format!(
+                "peer speaks protocol version {}, expected {}",
+                version, PROTOCOL_VERSION
+            )))
+        }
+        _ => return Err(network_error("peer did not start with a handshake")),
+    };
+    if chain_id != credentials.chain_id {
+        return Err(network_error(format!(
+            "peer runs chain {}, expected {}",
+            chain_id, credentials.chain_id
+        )));
+    }
+    if !credentials.registry.contains(&peer) {
+        return Err(network_error(format!(
+            "{} is not a genesis validator",
+            peer
+        )));
+    }
+    let signature = credentials.signing_key.sign(&handshake_bytes(
+        &credentials.chain_id,
+        &credentials.validator_id,
+        &nonce,
+        &peer,
+        &peer_nonce,
+    ));
+    write_frame(stream, &Message::Authenticate { signature })?;
+    match read_frame(stream)? {
+        Message::Authenticate { signature } => credentials
+            .registry
+            .verify(
+                &peer,
+                &handshake_bytes(
+                    &credentials.chain_id,
+                    &peer,
+                    &peer_nonce,
+                    &credentials.validator_id,
+                    &nonce,
+                ),
+                &signature,
+            )
+            .map_err(|_| network_error(format!("{} failed to prove its identity", peer)))?,
+        _ => return Err(network_error("peer did not authenticate")),
+    }
+    stream.set_read_timeout(None).map_err(network_error)?;
+    Ok(peer)
+}
+
+/// Moves [`Message`]s between a validator and its peers.
+pub trait Transport {
+    /// Returns the identifiers of the connected peers.
+    fn peers(&self) -> Vec<String>;
+
+    /// Sends `message` to `peer`, returning `false` if it is not connected.
+    fn send(&self, peer: &str, message: &Message) -> bool;
+
+    /// Waits up to `timeout` for a message from any peer, returning it with the sender's
+    /// identifier.
+    fn recv_timeout(&self, timeout: Duration) -> Option<(String, Message)>;
+}
+
+/// A registered connection: its number, the queue its writer thread drains and the stream, kept to
+/// shut the connection down.
+struct Connection {
+    id: u64,
+    queue: SyncSender<Message>,
+    stream: TcpStream,
+}
+
+impl Connection {
+    /// Queues `message` for the writer thread without waiting for room in the queue, returning
+    /// whether it was queued, or `None` if the connection is gone.
+    fn enqueue(&self, message: &Message) -> Option<bool> {
+        match self.queue.try_send(message.clone()) {
+            Ok(()) => Some(true),
+            Err(TrySendError::Full(_)) => Some(false),
+            Err(TrySendError::Disconnected(_)) => None,
+        }
+    }
+}
+
+/// Open connections by peer identifier, each tagged with a connection number so a closing
+/// connection only unregisters itself.
+type Peers = Arc<Mutex<HashMap<String, Connection>>>;
+
+/// What the accepting and dialing threads share with the transport.
+type Handles = (
+    Arc<Credentials>,
+    Peers,
+    SyncSender<(String, Message)>,
+    Arc<AtomicBool>,
+);
+
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(0);
+
+/// Connects a validator to its peers over TCP.
+///
+/// The transport accepts connections on its listening address and dials the peers it is told
+/// about, redialing whenever a connection fails or drops. Every connection starts with an
+/// authenticated [`handshake`], and only a few accepted connections may be handshaking at once;
+/// afterwards frames received from any peer are queued for [`TcpTransport::recv_timeout`], in a
+/// bounded queue that stops peers from being read while it is full, and
+/// [`TcpTransport::broadcast`] sends a frame to every connected peer. Each connection has its own writer thread draining a bounded queue, so sending never
+/// blocks on a slow peer: messages to a peer whose queue is full are dropped, and a peer that
+/// stops reading for longer than the write timeout is disconnected. Two validators that dial each
+/// other end up with two connections, of which the latest authenticated one is used for sending;
+/// both are read.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::block::Hash;
+/// use peo_consensus::crypto::{dev_signing_key, ValidatorRegistry};
+/// use peo_consensus::net::{Credentials, TcpTransport, Transport};
+/// use peo_consensus::node::Message;
+/// use std::time::Duration;
+///
+/// let mut registry = ValidatorRegistry::new();
+/// for id in ["v1", "v2"] {
+///     registry.register(id.to_string(), dev_signing_key(id).verifying_key());
+/// }
+/// let credentials = |id: &str| Credentials {
+///     chain_id: "peochain-devnet".to_string(),
+///     validator_id: id.to_string(),
+///     signing_key: dev_signing_key(id),
+///     registry: registry.clone(),
+/// };
+/// let a = TcpTransport::bind(credentials("v1"), "127.0.0.1:0").unwrap();
+/// let b = TcpTransport::bind(credentials("v2"), "127.0.0.1:0").unwrap();
+/// b.connect(a.local_addr().to_string());
+/// assert!(a.wait_for_peers(1, Duration::from_secs(5)));
+///
+/// let ping = Message::Hello {
+///     version: 0,
+///     chain_id: "peochain".to_string(),
+///     validator_id: "ping".to_string(),
+///     nonce: Hash::ZERO,
+/// };
+/// a.broadcast(&ping);
+/// let (from, message) = b.recv_timeout(Duration::from_secs(5)).unwrap();
+/// assert_eq!(from, "v1");
+/// assert_eq!(message, ping);
+/// ```
+pub struct TcpTransport {
+    credentials: Arc<Credentials>,
+    local_addr: SocketAddr,
+    peers: Peers,
+    closed: Arc<AtomicBool>,
+    sender: SyncSender<(String, Message)>,
+    inbox: Receiver<(String, Message)>,
+}
+
+impl TcpTransport {
+    /// Listens on `addr` for connections from peers, proving the local validator's identity with
+    /// `credentials`.
+    pub fn bind(
+        credentials: Credentials,
+        addr: impl ToSocketAddrs,
+    ) -> Result<Self, ConsensusError> {
+        let listener = TcpListener::bind(addr).map_err(network_error)?;
+        let local_addr = listener.local_addr().map_err(network_error)?;
+        let (sender, inbox) = mpsc::sync_channel(INBOX_LEN);
+        let transport = Self {
+            credentials: Arc::new(credentials),
+            local_addr,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            closed: Arc::new(AtomicBool::new(false)),
+            sender,
+            inbox,
+        };
+
+        let (id, peers, sender, closed) = transport.handles();
+        let handshaking = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                if closed.load(AtomicOrdering::Relaxed) {
+                    return;
+                }
+                // Connections cost a thread before they prove anything, so only a few may be
+                // unauthenticated at once.
+                if handshaking.fetch_add(1, AtomicOrdering::Relaxed) >= MAX_PENDING_HANDSHAKES {
+                    handshaking.fetch_sub(1, AtomicOrdering::Relaxed);
+                    let _ = stream.shutdown(Shutdown::Both);
+                    continue;
+                }
+                let (id, peers, sender) = (id.clone(), peers.clone(), sender.clone());
+                let handshaking = handshaking.clone();
+                thread::spawn(move || {
+                    let peer = authenticate(&mut stream, &id);
+                    handshaking.fetch_sub(1, AtomicOrdering::Relaxed);
+                    if let Ok(peer) = peer {
+                        let _ = run(stream, peer, &peers, &sender);
+                    }
+                });
+            }
+        });
+        Ok(transport)
+    }
+
+    fn handles(&self) -> Handles {
+        (
+            self.credentials.clone(),
+            self.peers.clone(),
+            self.sender.clone(),
+            self.closed.clone(),
+        )
+    }
+
+    /// Returns the address the transport listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Keeps a connection to the peer at `addr`, dialing it in the background until the transport
+    /// is dropped.
+    pub fn connect(&self, addr: String) {
+        let (id, peers, sender, closed) = self.handles();
+        thread::spawn(move || {
+            while !closed.load(AtomicOrdering::Relaxed) {
+                if let Ok(stream) = TcpStream::connect(&addr) {
+                    let _ = serve(stream, &id, &peers, &sender);
+                }
+                thread::sleep(REDIAL_INTERVAL);
+            }
+        });
+    }
+
+    /// Waits until at least `count` peers are connected, returning whether they are.
+    pub fn wait_for_peers(&self, count: usize, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.peers.lock().unwrap().len() < count {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        true
+    }
+
+    /// Queues `message` for every connected peer, dropping peers whose connection is gone.
+    pub fn broadcast(&self, message: &Message) {
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|_, connection| connection.enqueue(message).is_some());
+    }
+}
+
+impl Transport for TcpTransport {
+    fn peers(&self) -> Vec<String> {
+        self.peers.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn send(&self, peer: &str, message: &Message) -> bool {
+        let mut peers = self.peers.lock().unwrap();
+        let Some(connection) = peers.get(peer) else {
+            return false;
+        };
+        match connection.enqueue(message) {
+            Some(queued) => queued,
+            None => {
+                peers.remove(peer);
+                false
+            }
+        }
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Option<(String, Message)> {
+        self.inbox.recv_timeout(timeout).ok()
+    }
+}
+
+impl Drop for TcpTransport {
+    fn drop(&mut self) {
+        self.closed.store(true, AtomicOrdering::Relaxed);
+        for (_, connection) in self.peers.lock().unwrap().drain() {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+        // Wake the accept loop so it sees the transport is closed.
+        let _ = TcpStream::connect(self.local_addr);
+    }
+}
+
+/// Runs one connection: handshakes, then [`run`]s it.
+fn serve(
+    mut stream: TcpStream,
+    credentials: &Credentials,
+    peers: &Peers,
+    sender: &SyncSender<(String, Message)>,
+) -> Result<(), ConsensusError> {
+    let peer = authenticate(&mut stream, credentials)?;
+    run(stream, peer, peers, sender)
+}
+
+/// Handshakes on a new connection, returning the identifier of the peer, which must not be the
+/// local validator.
+fn authenticate(
+    stream: &mut TcpStream,
+    credentials: &Credentials,
+) -> Result<String, ConsensusError> {
+    stream.set_nodelay(true).map_err(network_error)?;
+    let peer = handshake(stream, credentials)?;
+    if peer == credentials.validator_id {
+        return Err(network_error("connected to self"));
+    }
+    Ok(peer)
+}
+
+/// Registers the authenticated `peer` for sending and forwards its frames to the inbox until the
+/// connection fails; a full inbox holds the reader back.
+///
+/// The connection replaces any earlier one to the same peer, which is left to be read until it
+/// closes; the peer has proven its identity, so a stale connection never shadows a live one.
+fn run(
+    mut stream: TcpStream,
+    peer: String,
+    peers: &Peers,
+    sender: &SyncSender<(String, Message)>,
+) -> Result<(), ConsensusError> {
+    let connection = NEXT_CONNECTION.fetch_add(1, AtomicOrdering::Relaxed);
+    let (queue, outgoing) = mpsc::sync_channel::<Message>(WRITE_QUEUE_LEN);
+    let mut writer = stream.try_clone().map_err(network_error)?;
+    thread::spawn(move || {
+        for message in outgoing {
+            if write_frame(&mut writer, &message).is_err() {
+                // Wakes the reader, which unregisters the connection.
+                let _ = writer.shutdown(Shutdown::Both);
+                return;
+            }
+        }
+    });
+    peers.lock().unwrap().insert(
+        peer.clone(),
+        Connection {
+            id: connection,
+            queue,
+            stream: stream.try_clone().map_err(network_error)?,
+        },
+    );
+
+    let result = loop {
+        match read_frame(&mut stream) {
+            Ok(message) => {
+                if sender.send((peer.clone(), message)).is_err() {
+                    break Ok(());
+                }
+            }
+            Err(e) => break Err(e),
+        }
+    };
+    let _ = stream.shutdown(Shutdown::Both);
+    let mut peers = peers.lock().unwrap();
+    if peers.get(&peer).is_some_and(|c| c.id == connection) {
+        peers.remove(&peer);
+    }
+    result
+}
+
+type Mailboxes = Arc<Mutex<HashMap<String, Sender<(String, Message)>>>>;
+
+/// An in-process network of [`MemoryTransport`]s, for simulating many nodes without sockets.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::block::Hash;
+/// use peo_consensus::net::{MemoryNetwork, Transport};
+/// use peo_consensus::node::Message;
+/// use std::time::Duration;
+///
+/// let network = MemoryNetwork::new();
+/// let mut a = network.join("a");
+/// let b = network.join("b");
+/// a.connect("b");
+/// assert_eq!(b.peers(), vec!["a".to_string()]);
+///
+/// let message = Message::Hello {
+///     version: 1,
+///     chain_id: "peochain".to_string(),
+///     validator_id: "a".to_string(),
+///     nonce: Hash::ZERO,
+/// };
+/// assert!(a.send("b", &message));
+/// assert_eq!(b.recv_timeout(Duration::ZERO), Some(("a".to_string(), message)));
+/// assert!(b.recv_timeout(Duration::ZERO).is_none());
+/// ```
+#[derive(Clone, Default)]
+pub struct MemoryNetwork {
+    mailboxes: Mailboxes,
+    links: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl MemoryNetwork {
+    /// Creates a network without any node.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node called `id`, returning its transport.
+    pub fn join(&self, id: &str) -> MemoryTransport {
+        let (sender, inbox) = mpsc::channel();
+        self.mailboxes
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), sender);
+        self.links
+            .lock()
+            .unwrap()
+            .entry(id.to_string())
+            .or_default();
+        MemoryTransport {
+            id: id.to_string(),
+            network: self.clone(),
+            inbox,
+        }
+    }
+}
+
+/// A node's end of a [`MemoryNetwork`].
+pub struct MemoryTransport {
+    id: String,
+    network: MemoryNetwork,
+    inbox: Receiver<(String, Message)>,
+}
+
+impl MemoryTransport {
+    /// Links this node with `peer` in both directions.
+    pub fn connect(&mut self, peer: &str) {
+        if peer == self.id {
+            return;
+        }
+        let mut links = self.network.links.lock().unwrap();
+        for (a, b) in [(&self.id, peer), (&peer.to_string(), self.id.as_str())] {
+            let peers = links.entry(a.clone()).or_default();
+            if !peers.iter().any(|p| p == b) {
+                peers.push(b.to_string());
+            }
+        }
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn peers(&self) -> Vec<String> {
+        self.network
+            .links
+            .lock()
+            .unwrap()
+            .get(&self.id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn send(&self, peer: &str, message: &Message) -> bool {
+        if !self.peers().iter().any(|p| p == peer) {
+            return false;
+        }
+        let mailboxes = self.network.mailboxes.lock().unwrap();
+        mailboxes
+            .get(peer)
+            .is_some_and(|mailbox| mailbox.send((self.id.clone(), message.clone())).is_ok())
+    }
+
+    fn recv_timeout(&self, timeout: Duration) -> Option<(String, Message)> {
+        if timeout.is_zero() {
+            return self.inbox.try_recv().ok();
+        }
+        self.inbox.recv_timeout(timeout).ok()
+    }
+}
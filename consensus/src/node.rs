@@ -0,0 +1,1034 @@
+// src/node.rs
+
+use crate::block::{Block, Hash};
+use crate::crypto::{Signature, VerifyingKey};
+use crate::events::{ConsensusEvent, EventBus, Observer};
+use crate::evidence::{Evidence, EvidencePool, EVIDENCE_MAX_AGE};
+use crate::fork_choice::{BlockTree, ReorgEvent};
+use crate::mempool::Mempool;
+use crate::scoring::ScoreInputs;
+use crate::timeout::TimeoutConfig;
+use crate::transaction::Transaction;
+use crate::vote::{has_supermajority, QuorumCertificate, Vote, VoteSet, VoteType};
+use crate::{ConsensusEngine, ConsensusError, PosygDcsEngine, BLOCK_INTERVAL_MS};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::sync::Arc;
+
+/// How many heights past the current one a node keeps messages for.
+const FUTURE_HEIGHTS: u64 = 1;
+
+/// How many rounds past the current one a node keeps messages for.
+const FUTURE_ROUNDS: u32 = 8;
+
+/// Most certified blocks a node sends in answer to one [`Message::SyncRequest`].
+pub const MAX_SYNC_BLOCKS: usize = 64;
+
+/// A message exchanged between consensus nodes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// First message on every connection, announcing the sender and its protocol version.
+    Hello {
+        /// Protocol version spoken by the sender.
+        version: u32,
+        /// Identifier of the chain the sender runs.
+        chain_id: String,
+        /// Identifier of the sending validator.
+        validator_id: String,
+        /// Random challenge the receiver must sign to prove its identity.
+        nonce: Hash,
+    },
+    /// Answer to a peer's [`Message::Hello`], signing its nonce with the sender's genesis key.
+    Authenticate {
+        /// Signature over the peer's nonce and the sender's identifier.
+        signature: Signature,
+    },
+    /// A signed block proposed in `round`.
+    Proposal {
+        /// Round in which the block is proposed.
+        round: u32,
+        /// The proposed block.
+        block: Block,
+        /// Prevotes of more than two thirds of the stake for the block in an earlier round, when
+        /// the block is proposed again; empty for a new block.
+        pol: Vec<Vote>,
+    },
+    /// A signed prevote or precommit.
+    Vote(Vote),
+    /// Asks a peer for the certified blocks from `height` on, sent to peers directly rather than
+    /// gossiped.
+    SyncRequest {
+        /// Height of the first block wanted: the sender's current height.
+        height: u64,
+    },
+    /// Answers a [`Message::SyncRequest`] with consecutive blocks and the certificates that
+    /// finalized them.
+    SyncResponse {
+        /// Blocks in height order, each with its certificate.
+        blocks: Vec<(Block, QuorumCertificate)>,
+    },
+    /// Proof that a validator equivocated, relayed to every node.
+    Evidence(Evidence),
+}
+
+/// A member of the validator set a [`Node`] runs consensus with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorInfo {
+    /// Identifier of the validator.
+    pub id: String,
+    /// Stake the validator votes with.
+    pub stake: u64,
+    /// Key the validator's proposals and votes must be signed with.
+    pub key: VerifyingKey,
+}
+
+/// What a node has seen of a validator's proposals.
+///
+/// A node only learns the outcome of other validators' proposals, so their synergy scores count
+/// proposal outcomes alone, without the validation contribution each validator earns locally.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorRecord {
+    /// Synergy score of the validator.
+    pub synergy_score: f64,
+    /// Invalid blocks the validator proposed.
+    pub violations: u64,
+    /// Rounds the validator was the proposer of.
+    pub proposed: u64,
+    /// Blocks of the validator that were committed.
+    pub accepted: u64,
+}
+
+/// The consensus state machine of a single validator talking to its peers over messages.
+///
+/// Unlike [`Network`](crate::Network), which runs every validator in one process, a node only
+/// controls its own validator. It is fed every [`Message`] received from peers through
+/// [`Node::handle`] and returns the messages it wants broadcast, so it can run over any transport.
+/// Its own messages are applied locally before being returned.
+///
+/// The proposer of each height and round is drawn by stake from a hash of the parent block and the
+/// round, so every node agrees on it without coordination. A round ends when more than two thirds
+/// of the stake precommits to the proposal, which is then committed, or to nil, in which case the
+/// next round starts. Verified messages for the next few heights or rounds are kept until the node
+/// gets there, and anything further ahead is dropped, except that a node seeing more than a third
+/// of the stake vote in a later round of its height jumps to that round, since it has fallen
+/// behind.
+///
+/// Nodes lock as in Tendermint. A node that precommits a block locks on it and from then on
+/// prevotes nil on any other proposal of the height, unless the proposal carries a proof of lock:
+/// prevotes of more than two thirds of the stake for it in a round no earlier than the node's lock.
+/// A node also remembers the latest block it saw gather such prevotes as its valid block, and
+/// proposes that block again, with its proof of lock, instead of a new one. Any validator may
+/// propose a block again once it has a proof of lock, so the block keeps its original proposer.
+///
+/// A node does not keep time itself: the driver calls [`Node::timeout`] once
+/// [`Node::round_timeout_ms`] has elapsed without the node leaving the round, and the node then
+/// votes nil to skip the round's proposer. Once more than two thirds of the stake has precommitted
+/// without agreeing, the node waits a precommit timeout for the rest, restarted by the driver when
+/// [`Node::awaiting_precommits`] turns true, and then moves on to the next round.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::dev_signing_key;
+/// use peo_consensus::node::{Node, ValidatorInfo};
+/// use peo_consensus::PosygDcsEngine;
+///
+/// let set: Vec<ValidatorInfo> = ["v1", "v2"]
+///     .iter()
+///     .map(|id| {
+///         let key = dev_signing_key(id).verifying_key();
+///         ValidatorInfo { id: id.to_string(), stake: 1000, key }
+///     })
+///     .collect();
+/// let mut nodes: Vec<Node> = ["v1", "v2"]
+///     .iter()
+///     .map(|id| {
+///         let key = dev_signing_key(id);
+///         let engine = PosygDcsEngine::with_signing_key(id.to_string(), 1000, false, key);
+///         Node::new(engine, set.clone()).unwrap()
+///     })
+///     .collect();
+///
+/// // Deliver every message to every other node until the first block is final.
+/// let mut in_flight: Vec<(usize, _)> = Vec::new();
+/// for (i, node) in nodes.iter_mut().enumerate() {
+///     in_flight.extend(node.start().into_iter().map(|m| (i, m)));
+/// }
+/// while nodes.iter().any(|node| node.height() < 2) {
+///     let (from, message) = in_flight.remove(0);
+///     for (i, node) in nodes.iter_mut().enumerate() {
+///         if i != from {
+///             in_flight.extend(node.handle(message.clone()).into_iter().map(|m| (i, m)));
+///         }
+///     }
+/// }
+/// assert_eq!(nodes[0].head().hash(), nodes[1].head().hash());
+/// assert!(nodes[0].certificate(1).is_some());
+/// ```
+pub struct Node {
+    engine: PosygDcsEngine,
+    validators: Vec<ValidatorInfo>,
+    total_stake: u64,
+    chain: Vec<Block>,
+    tree: BlockTree,
+    certificates: BTreeMap<u64, QuorumCertificate>,
+    round: u32,
+    proposal: Option<Block>,
+    prevotes: VoteSet,
+    precommits: VoteSet,
+    prevoted: bool,
+    precommitted: bool,
+    awaiting_precommits: bool,
+    future: Vec<Message>,
+    future_voters: BTreeMap<(u64, u32), BTreeSet<String>>,
+    inbox: VecDeque<Message>,
+    outbox: Vec<Message>,
+    mempool: Mempool,
+    timeouts: TimeoutConfig,
+    records: BTreeMap<String, ValidatorRecord>,
+    invalid_proposals: HashSet<u32>,
+    locked_block: Option<Block>,
+    locked_round: Option<u32>,
+    valid_block: Option<Block>,
+    valid_round: Option<u32>,
+    valid_pol: Vec<Vote>,
+    events: EventBus,
+    evidence: EvidencePool,
+    fault: Option<ConsensusError>,
+}
+
+impl Node {
+    /// Creates a node for `engine`'s validator, running consensus with `validators` from genesis.
+    ///
+    /// The engine learns the keys of the whole set. Fails if the set is empty, has no stake,
+    /// repeats an identifier or does not include the engine's validator.
+    pub fn new(
+        mut engine: PosygDcsEngine,
+        mut validators: Vec<ValidatorInfo>,
+    ) -> Result<Self, ConsensusError> {
+        if validators.is_empty() {
+            return Err(ConsensusError::EmptyValidatorSet);
+        }
+        validators.sort_by(|a, b| a.id.cmp(&b.id));
+        if validators.windows(2).any(|pair| pair[0].id == pair[1].id) {
+            return Err(ConsensusError::DuplicateValidator);
+        }
+        if !validators.iter().any(|v| v.id == engine.validator_id()) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        let total_stake: u64 = validators.iter().map(|v| v.stake).sum();
+        if total_stake == 0 {
+            return Err(ConsensusError::InvalidStake);
+        }
+        for validator in &validators {
+            engine.register_validator(validator.id.clone(), validator.key);
+        }
+
+        let genesis = Block::genesis();
+        let height = genesis.header.height + 1;
+        Ok(Self {
+            engine,
+            validators,
+            total_stake,
+            tree: BlockTree::new(genesis.clone()),
+            chain: vec![genesis],
+            certificates: BTreeMap::new(),
+            round: 0,
+            proposal: None,
+            prevotes: VoteSet::new(VoteType::Prevote, height, 0, total_stake as f64),
+            precommits: VoteSet::new(VoteType::Precommit, height, 0, total_stake as f64),
+            prevoted: false,
+            precommitted: false,
+            awaiting_precommits: false,
+            future: Vec::new(),
+            future_voters: BTreeMap::new(),
+            inbox: VecDeque::new(),
+            outbox: Vec::new(),
+            mempool: Mempool::default(),
+            timeouts: TimeoutConfig::default(),
+            records: BTreeMap::new(),
+            invalid_proposals: HashSet::new(),
+            locked_block: None,
+            locked_round: None,
+            valid_block: None,
+            valid_round: None,
+            valid_pol: Vec::new(),
+            events: EventBus::new(),
+            evidence: EvidencePool::new(),
+            fault: None,
+        })
+    }
+
+    /// Returns the identifier of the node's validator.
+    pub fn validator_id(&self) -> &str {
+        self.engine.validator_id()
+    }
+
+    /// Returns the node's validator.
+    pub fn engine(&self) -> &PosygDcsEngine {
+        &self.engine
+    }
+
+    /// Returns the validator set, ordered by identifier.
+    pub fn validators(&self) -> &[ValidatorInfo] {
+        &self.validators
+    }
+
+    /// Returns the height the node is currently deciding.
+    pub fn height(&self) -> u64 {
+        self.head().header.height + 1
+    }
+
+    /// Returns the round the node is currently in.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Returns the committed chain, starting with the genesis block.
+    pub fn chain(&self) -> &[Block] {
+        &self.chain
+    }
+
+    /// Returns the last committed block.
+    pub fn head(&self) -> &Block {
+        self.chain
+            .last()
+            .expect("chain always contains the genesis block")
+    }
+
+    /// Returns the tree of committed blocks descending from the last finalized one, whose
+    /// canonical chain the node follows.
+    pub fn block_tree(&self) -> &BlockTree {
+        &self.tree
+    }
+
+    /// Returns the certificate that finalized the block at `height`, if any.
+    pub fn certificate(&self, height: u64) -> Option<&QuorumCertificate> {
+        self.certificates.get(&height)
+    }
+
+    /// Returns what the node has seen of validator `id`'s proposals.
+    ///
+    /// The record of the node's own validator comes from its engine, including the validation
+    /// contribution to its score.
+    pub fn record(&self, id: &str) -> ValidatorRecord {
+        if id == self.engine.validator_id() {
+            return ValidatorRecord {
+                synergy_score: self.engine.get_synergy_score(),
+                violations: self.engine.violations(),
+                proposed: self.engine.proposed_blocks(),
+                accepted: self.engine.accepted_blocks(),
+            };
+        }
+        self.records.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Returns whether the node has received the proposal of the current round.
+    pub fn has_proposal(&self) -> bool {
+        self.proposal.is_some()
+    }
+
+    /// Returns whether the node has prevoted and precommitted in the current round.
+    pub fn has_voted(&self) -> (bool, bool) {
+        (self.prevoted, self.precommitted)
+    }
+
+    /// Returns the round in which the node locked on a block of the current height, if it did.
+    pub fn locked_round(&self) -> Option<u32> {
+        self.locked_round
+    }
+
+    /// Returns the block the node is locked on at the current height, if any.
+    pub fn locked_block(&self) -> Option<&Block> {
+        self.locked_block.as_ref()
+    }
+
+    /// Returns the latest round of the current height in which the node saw more than two thirds
+    /// of the stake prevote the round's proposal, if it did.
+    pub fn valid_round(&self) -> Option<u32> {
+        self.valid_round
+    }
+
+    /// Returns the block the node proposes again at the current height, if any.
+    pub fn valid_block(&self) -> Option<&Block> {
+        self.valid_block.as_ref()
+    }
+
+    /// Returns the transactions this node includes in the blocks it proposes.
+    pub fn mempool(&self) -> &Mempool {
+        &self.mempool
+    }
+
+    /// Submits `transaction` to the node's mempool, returning its hash.
+    ///
+    /// The transaction must be admissible against the account state of the node's validator. It is
+    /// proposed once this node's validator is the proposer, and leaves the mempool when a block
+    /// including it is committed.
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Result<Hash, ConsensusError> {
+        self.engine.accounts().check_admissible(&transaction)?;
+        self.mempool.submit(transaction)
+    }
+
+    /// Returns the verified evidence of equivocation the node has detected in the votes it received
+    /// or had relayed to it, oldest first.
+    ///
+    /// Blocks do not carry evidence, so nodes do not slash on their own: the evidence is kept for
+    /// submission to the chain's [`Network::submit_evidence`](crate::Network::submit_evidence).
+    pub fn evidence(&self) -> &[Evidence] {
+        self.evidence.pending()
+    }
+
+    /// Submits `evidence` to the node, returning the messages that relay it to every peer.
+    ///
+    /// The evidence must verify against the validators' keys, concern a member of the validator
+    /// set and be at most [`EVIDENCE_MAX_AGE`] heights behind the current one; evidence the node
+    /// already holds for the same slot fails with [`ConsensusError::DuplicateEvidence`].
+    pub fn submit_evidence(&mut self, evidence: Evidence) -> Result<Vec<Message>, ConsensusError> {
+        evidence.verify(self.engine.registry())?;
+        if !self.validators.iter().any(|v| v.id == evidence.validator()) {
+            return Err(ConsensusError::UnknownValidator);
+        }
+        if evidence.height() < self.evidence_horizon() {
+            return Err(ConsensusError::ExpiredEvidence);
+        }
+        if !self.evidence.insert(evidence.clone()) {
+            return Err(ConsensusError::DuplicateEvidence);
+        }
+        Ok(vec![Message::Evidence(evidence)])
+    }
+
+    /// Returns the lowest height evidence is still accepted for.
+    fn evidence_horizon(&self) -> u64 {
+        self.height().saturating_sub(EVIDENCE_MAX_AGE)
+    }
+
+    /// Sets how long the node waits for proposals.
+    pub fn set_timeout_config(&mut self, config: TimeoutConfig) {
+        self.timeouts = config;
+    }
+
+    /// Returns how long the node waits in its current step, in milliseconds: for the round's
+    /// proposal, or, once it is [`awaiting_precommits`](Self::awaiting_precommits), for the
+    /// round's remaining precommits.
+    pub fn round_timeout_ms(&self) -> u64 {
+        if self.awaiting_precommits {
+            self.timeouts.precommit_timeout(self.round)
+        } else {
+            self.timeouts.propose_timeout(self.round)
+        }
+    }
+
+    /// Returns whether more than two thirds of the stake has precommitted in the current round
+    /// without agreeing on a value, so the node is waiting for the rest before giving up on it.
+    ///
+    /// The driver restarts its timer when the node starts waiting.
+    pub fn awaiting_precommits(&self) -> bool {
+        self.awaiting_precommits
+    }
+
+    /// Returns the error that halted the node, if any.
+    ///
+    /// A node halts with [`ConsensusError::InvariantViolated`] when it cannot commit a certified
+    /// block, which would mean its state no longer follows its chain; it then ignores every
+    /// message and timeout, and should be restarted from a fresh state.
+    pub fn fault(&self) -> Option<&ConsensusError> {
+        self.fault.as_ref()
+    }
+
+    /// Gives up on the current step, returning the messages to broadcast.
+    ///
+    /// A node [`awaiting_precommits`](Self::awaiting_precommits) moves on to the next round.
+    /// Otherwise, unless it already did, the node prevotes nil to skip the proposer and precommits
+    /// nil. Once more than two thirds of the stake has done the same, every node moves to the next
+    /// round, which has a longer timeout. A node that had already cast both votes sends them again,
+    /// for peers that missed them while crashed or cut off; the resent votes are identical to the
+    /// originals, so a [`Gossip`](crate::gossip::Gossip) router sends them to every peer, which
+    /// accept them from their signer despite having seen them.
+    pub fn timeout(&mut self) -> Vec<Message> {
+        if self.fault.is_some() {
+            return Vec::new();
+        }
+        if self.awaiting_precommits {
+            self.enter_round(self.round + 1);
+            return self.drain();
+        }
+        let (height, round) = (self.height(), self.round);
+        if self.prevoted && self.precommitted {
+            let id = self.engine.validator_id();
+            let own = self
+                .prevotes
+                .votes()
+                .chain(self.precommits.votes())
+                .filter(|vote| vote.validator == id);
+            let votes: Vec<Message> = own.cloned().map(Message::Vote).collect();
+            self.outbox.extend(votes);
+        }
+        if !self.prevoted {
+            self.prevoted = true;
+            let prevote = self.engine.skip_prevote(height, round);
+            self.broadcast(Message::Vote(prevote));
+        }
+        if !self.precommitted {
+            self.precommitted = true;
+            let precommit = self.engine.precommit(height, round, None);
+            self.broadcast(Message::Vote(precommit));
+        }
+        self.drain()
+    }
+
+    /// Adds an observer notified of every block the node commits, with
+    /// [`ConsensusEvent::BlockFinalized`], and of every proposal it sees rejected, with
+    /// [`ConsensusEvent::BlockRejected`].
+    pub fn subscribe(&mut self, observer: Arc<dyn Observer>) {
+        self.events.subscribe(observer);
+    }
+
+    /// Returns the validator expected to propose in `round` of the current height.
+    pub fn proposer(&self, round: u32) -> &str {
+        let mut seed = self.head().hash().as_bytes().to_vec();
+        seed.extend_from_slice(&round.to_be_bytes());
+        let digest = Hash::digest(&seed);
+        let mut point =
+            u64::from_be_bytes(digest.as_bytes()[..8].try_into().unwrap()) % self.total_stake;
+        for validator in &self.validators {
+            if point < validator.stake {
+                return &validator.id;
+            }
+            point -= validator.stake;
+        }
+        unreachable!("point is below the total stake")
+    }
+
+    /// Commits `block` finalized by `certificate` at the node's current height, returning the
+    /// messages to broadcast as the node moves on to the next height.
+    ///
+    /// This is how a node that missed the messages of a height, while down or cut off, catches up
+    /// from a peer: the certificate must be signed by validators holding more than two thirds of
+    /// the stake and the block must be valid.
+    pub fn sync(
+        &mut self,
+        block: Block,
+        certificate: QuorumCertificate,
+    ) -> Result<Vec<Message>, ConsensusError> {
+        self.sync_block(block, certificate)?;
+        Ok(self.drain())
+    }
+
+    /// Returns the request to send peers to learn the blocks the node may have missed.
+    pub fn sync_request(&self) -> Message {
+        Message::SyncRequest {
+            height: self.height(),
+        }
+    }
+
+    /// Answers a peer's request for the certified blocks from `height` on, returning `None` if the
+    /// node has none of them.
+    ///
+    /// At most [`MAX_SYNC_BLOCKS`] blocks are sent; the peer asks again for the rest.
+    pub fn sync_response(&self, height: u64) -> Option<Message> {
+        let blocks: Vec<_> = (height..self.height())
+            .take(MAX_SYNC_BLOCKS)
+            .map_while(|height| {
+                let certificate = self.certificate(height)?.clone();
+                Some((self.chain[height as usize].clone(), certificate))
+            })
+            .collect();
+        (!blocks.is_empty()).then_some(Message::SyncResponse { blocks })
+    }
+
+    fn sync_block(
+        &mut self,
+        block: Block,
+        certificate: QuorumCertificate,
+    ) -> Result<(), ConsensusError> {
+        if block.header.height != self.height() || certificate.height != self.height() {
+            return Err(ConsensusError::InvalidHeight);
+        }
+        if certificate.block_hash != block.hash() {
+            return Err(ConsensusError::InvalidBlock);
+        }
+        let stakes = self
+            .validators
+            .iter()
+            .map(|v| (v.id.clone(), v.stake))
+            .collect();
+        certificate.verify(self.engine.registry(), &stakes)?;
+        self.engine.validate_block(&block)?;
+        self.commit(block, certificate)
+    }
+
+    /// Starts consensus at the first height, returning the messages to broadcast.
+    pub fn start(&mut self) -> Vec<Message> {
+        self.enter_round(0);
+        self.drain()
+    }
+
+    /// Processes a message received from a peer, returning the messages to broadcast in response.
+    ///
+    /// A [`Message::SyncRequest`] is answered to its sender alone, with
+    /// [`sync_response`](Self::sync_response), so it is ignored here.
+    pub fn handle(&mut self, message: Message) -> Vec<Message> {
+        self.inbox.push_back(message);
+        self.drain()
+    }
+
+    fn drain(&mut self) -> Vec<Message> {
+        while let Some(message) = self.inbox.pop_front() {
+            self.step(message);
+        }
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn broadcast(&mut self, message: Message) {
+        self.outbox.push(message.clone());
+        self.inbox.push_back(message);
+    }
+
+    fn step(&mut self, message: Message) {
+        if self.fault.is_some() {
+            return;
+        }
+        if let Message::SyncResponse { blocks } = message {
+            // Blocks the node already has are skipped, and the first one that fails stops the rest.
+            for (block, certificate) in blocks {
+                if block.header.height < self.height() {
+                    continue;
+                }
+                match self.sync_block(block, certificate) {
+                    Ok(()) => {}
+                    Err(error @ ConsensusError::InvariantViolated(_)) => {
+                        self.fault = Some(error);
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+            return;
+        }
+        if let Message::Evidence(evidence) = message {
+            // Gossip relays fresh evidence to the other peers; invalid or known evidence is dropped.
+            let _ = self.submit_evidence(evidence);
+            return;
+        }
+        let Some((height, round)) = position(&message) else {
+            return;
+        };
+        match (height, round).cmp(&(self.height(), self.round)) {
+            Ordering::Less => return,
+            Ordering::Greater => {
+                self.buffer(message, height, round);
+                if height == self.height() && self.round_is_ahead(round) {
+                    self.enter_round(round);
+                }
+                return;
+            }
+            Ordering::Equal => {}
+        }
+        match message {
+            Message::Proposal { block, pol, .. } => self.on_proposal(block, pol),
+            Message::Vote(vote) => self.on_vote(vote),
+            _ => {}
+        }
+    }
+
+    fn on_proposal(&mut self, block: Block, pol: Vec<Vote>) {
+        if self.proposal.is_some() {
+            return;
+        }
+        let pol_round = if pol.is_empty() {
+            if block.header.proposer != self.proposer(self.round) {
+                return;
+            }
+            None
+        } else {
+            let Some(round) = self.pol_round(&block, &pol) else {
+                return;
+            };
+            Some(round)
+        };
+        if self.engine.validate_block(&block).is_err() {
+            self.invalid_proposals.insert(self.round);
+        }
+        // A node that already voted to skip the proposer keeps the block, in case it is committed
+        // anyway, but does not vote twice.
+        if !self.prevoted {
+            self.prevoted = true;
+            // A proof of lock from the node's locking round or later releases the lock; a proposal
+            // without one has no round, which compares below any.
+            let unlocked = match &self.locked_block {
+                None => true,
+                Some(locked) => locked.hash() == block.hash() || pol_round >= self.locked_round,
+            };
+            let prevote = if unlocked {
+                self.engine.prevote(&block, self.round)
+            } else {
+                self.engine.skip_prevote(self.height(), self.round)
+            };
+            self.broadcast(Message::Vote(prevote));
+        }
+        self.proposal = Some(block);
+        self.check_polka();
+        self.check_commit();
+    }
+
+    /// Returns the round of `pol` if it holds verified prevotes of more than two thirds of the
+    /// stake for `block`, in a single round before the current one.
+    fn pol_round(&self, block: &Block, pol: &[Vote]) -> Option<u32> {
+        let round = pol[0].round;
+        if round >= self.round {
+            return None;
+        }
+        let total = self.total_stake as f64;
+        let mut prevotes = VoteSet::new(VoteType::Prevote, block.header.height, round, total);
+        for vote in pol {
+            let weight = self
+                .validators
+                .iter()
+                .find(|v| v.id == vote.validator)?
+                .stake;
+            vote.verify_signature(self.engine.registry()).ok()?;
+            prevotes.add(vote.clone(), weight as f64);
+        }
+        (prevotes.quorum() == Some(Some(block.hash()))).then_some(round)
+    }
+
+    /// Acts on more than two thirds of the stake prevoting in the current round: the node
+    /// precommits nil on a nil quorum, and on a quorum for the valid proposal it holds it makes the
+    /// proposal its valid block and, unless it already precommitted, locks on it and precommits it.
+    fn check_polka(&mut self) {
+        let Some(polka) = self.prevotes.quorum() else {
+            return;
+        };
+        let (height, round) = (self.height(), self.round);
+        let Some(hash) = polka else {
+            if !self.precommitted {
+                self.precommitted = true;
+                let precommit = self.engine.precommit(height, round, None);
+                self.broadcast(Message::Vote(precommit));
+            }
+            return;
+        };
+        // A node precommits only a block it holds and finds valid; it waits for the proposal
+        // otherwise, and precommits nil if it times out first.
+        let Some(block) = self
+            .proposal
+            .clone()
+            .filter(|block| block.hash() == hash && !self.invalid_proposals.contains(&round))
+        else {
+            return;
+        };
+        if self.valid_round != Some(round) {
+            self.valid_pol = self
+                .prevotes
+                .votes()
+                .filter(|vote| vote.block_hash == Some(hash))
+                .cloned()
+                .collect();
+            self.valid_block = Some(block.clone());
+            self.valid_round = Some(round);
+        }
+        if !self.precommitted {
+            self.precommitted = true;
+            self.locked_block = Some(block);
+            self.locked_round = Some(round);
+            let precommit = self.engine.precommit(height, round, Some(hash));
+            self.broadcast(Message::Vote(precommit));
+        }
+    }
+
+    fn on_vote(&mut self, vote: Vote) {
+        let Some(weight) = self
+            .validators
+            .iter()
+            .find(|v| v.id == vote.validator)
+            .map(|v| v.stake)
+        else {
+            return;
+        };
+        if vote.verify_signature(self.engine.registry()).is_err() {
+            return;
+        }
+        if let Some(evidence) = self.evidence.observe(&vote) {
+            self.outbox.push(Message::Evidence(evidence));
+        }
+        match vote.vote_type {
+            VoteType::Prevote => {
+                self.prevotes.add(vote, weight as f64);
+                self.check_polka();
+            }
+            VoteType::Precommit => {
+                self.precommits.add(vote, weight as f64);
+                let round = (self.height(), self.round);
+                self.check_commit();
+                if round == (self.height(), self.round)
+                    && self.precommits.quorum().is_none()
+                    && has_supermajority(self.precommits.cast_weight(), self.total_stake as f64)
+                {
+                    self.awaiting_precommits = true;
+                }
+            }
+        }
+    }
+
+    fn check_commit(&mut self) {
+        match self.precommits.quorum() {
+            Some(Some(hash)) => {
+                let Some(block) = self.proposal.take_if(|block| block.hash() == hash) else {
+                    // The block is committed once its proposal arrives.
+                    return;
+                };
+                if let Some(certificate) = self.precommits.certificate(hash) {
+                    if let Err(error) = self.commit(block, certificate) {
+                        self.fault = Some(error);
+                    }
+                }
+            }
+            Some(None) => {
+                if let Some(block) = &self.proposal {
+                    self.events.emit(ConsensusEvent::BlockRejected {
+                        height: block.header.height,
+                        round: self.round,
+                        proposer: block.header.proposer.clone(),
+                        hash: block.hash(),
+                    });
+                }
+                if self.proposer(self.round) == self.engine.validator_id() {
+                    let violation = self.invalid_proposals.contains(&self.round);
+                    self.engine.update_scores(false, violation);
+                    self.engine.increment_proposed_blocks();
+                }
+                self.enter_round(self.round + 1);
+            }
+            None => {}
+        }
+    }
+
+    /// Commits a certified block, failing with [`ConsensusError::InvariantViolated`] if the node's
+    /// state cannot follow it.
+    fn commit(
+        &mut self,
+        block: Block,
+        certificate: QuorumCertificate,
+    ) -> Result<(), ConsensusError> {
+        let (hash, height) = (block.hash(), block.header.height);
+        let weight = self.signed_stake(&certificate) as f64;
+        let Ok(event) = self.tree.insert(block, weight) else {
+            return Ok(());
+        };
+        self.certificates.insert(height, certificate);
+        if let Some(event) = event {
+            self.apply_reorg(event)?;
+        }
+        if let Ok(Some(event)) = self.tree.finalize(hash) {
+            self.apply_reorg(event)?;
+        }
+        self.evidence.prune_below(self.evidence_horizon());
+        self.invalid_proposals.clear();
+        self.locked_block = None;
+        self.locked_round = None;
+        self.valid_block = None;
+        self.valid_round = None;
+        self.valid_pol.clear();
+        self.enter_round(0);
+        Ok(())
+    }
+
+    /// Moves the node's state onto the new canonical head, committing every applied block.
+    ///
+    /// A block is finalized as soon as it is certified, so the head only ever moves forward.
+    ///
+    /// Fails with [`ConsensusError::InvariantViolated`] if the event reverts a block or the engine
+    /// cannot commit an applied one.
+    fn apply_reorg(&mut self, event: ReorgEvent) -> Result<(), ConsensusError> {
+        if let Some(block) = event.reverted.first() {
+            return Err(ConsensusError::InvariantViolated(format!(
+                "finalized block {} was reverted",
+                block.hash()
+            )));
+        }
+        for block in event.applied {
+            self.engine.commit_block(&block).map_err(|e| {
+                ConsensusError::InvariantViolated(format!(
+                    "cannot commit block {} on the node's head: {:?}",
+                    block.hash(),
+                    e
+                ))
+            })?;
+            // The proposers of the rounds before the one that committed were skipped. The proposer
+            // draw depends on the parent, so it is made before the block joins the chain.
+            let round = self
+                .certificates
+                .get(&block.header.height)
+                .map_or(0, |certificate| certificate.round);
+            let skipped: Vec<(String, bool)> = (0..round)
+                .map(|round| {
+                    (
+                        self.proposer(round).to_string(),
+                        self.invalid_proposals.contains(&round),
+                    )
+                })
+                .collect();
+            for (proposer, violation) in skipped {
+                self.record_proposal(&proposer, false, violation);
+            }
+            self.record_proposal(&block.header.proposer, true, false);
+            if block.header.proposer == self.engine.validator_id() {
+                self.engine.update_scores(true, false);
+                self.engine.increment_accepted_blocks();
+                self.engine.increment_proposed_blocks();
+            }
+            self.mempool.remove_included(&block.transactions);
+            self.mempool.remove_stale(self.engine.accounts());
+            let event = ConsensusEvent::BlockFinalized {
+                height: block.header.height,
+                round,
+                proposer: block.header.proposer.clone(),
+                hash: block.hash(),
+            };
+            self.chain.push(block);
+            self.events.emit(event);
+        }
+        Ok(())
+    }
+
+    /// Returns the stake of the validators whose precommits `certificate` carries.
+    fn signed_stake(&self, certificate: &QuorumCertificate) -> u64 {
+        let signers: HashSet<&str> = certificate
+            .precommits
+            .iter()
+            .map(|vote| vote.validator.as_str())
+            .collect();
+        self.validators
+            .iter()
+            .filter(|v| signers.contains(v.id.as_str()))
+            .map(|v| v.stake)
+            .sum()
+    }
+
+    fn record_proposal(&mut self, id: &str, accepted: bool, violation: bool) {
+        let stake = self
+            .validators
+            .iter()
+            .find(|v| v.id == id)
+            .map_or(0, |v| v.stake);
+        let policy = self.engine.scoring_policy().clone();
+        let record = self.records.entry(id.to_string()).or_default();
+        record.proposed += 1;
+        if accepted {
+            record.accepted += 1;
+        }
+        if violation {
+            record.violations += 1;
+        }
+        record.synergy_score += policy.proposal_delta(&ScoreInputs {
+            block_accepted: accepted,
+            stake,
+            validation: 0.0,
+            violation_occurred: violation,
+            violations: record.violations,
+        });
+    }
+
+    /// Keeps `message`, for a later `height` and `round`, until the node gets there.
+    ///
+    /// Only messages within [`FUTURE_HEIGHTS`] heights and [`FUTURE_ROUNDS`] rounds of the node's
+    /// position are kept, and only once their signature verifies, so a peer cannot fill the
+    /// buffer with messages the node may never need.
+    fn buffer(&mut self, message: Message, height: u64, round: u32) {
+        let first_round = if height == self.height() {
+            self.round
+        } else {
+            0
+        };
+        let within =
+            height <= self.height() + FUTURE_HEIGHTS && round <= first_round + FUTURE_ROUNDS;
+        if !within || self.future.contains(&message) {
+            return;
+        }
+        match &message {
+            Message::Vote(vote) => {
+                let known = self.validators.iter().any(|v| v.id == vote.validator);
+                if !known || vote.verify_signature(self.engine.registry()).is_err() {
+                    return;
+                }
+                self.future_voters
+                    .entry((height, round))
+                    .or_default()
+                    .insert(vote.validator.clone());
+            }
+            Message::Proposal { block, .. } => {
+                if block.verify_signature(self.engine.registry()).is_err() {
+                    return;
+                }
+            }
+            _ => return,
+        }
+        self.future.push(message);
+    }
+
+    /// Returns whether validators holding more than a third of the stake have sent verified votes
+    /// for `round` of the current height.
+    fn round_is_ahead(&self, round: u32) -> bool {
+        let Some(voters) = self.future_voters.get(&(self.height(), round)) else {
+            return false;
+        };
+        let weight: u64 = self
+            .validators
+            .iter()
+            .filter(|v| voters.contains(&v.id))
+            .map(|v| v.stake)
+            .sum();
+        weight * 3 > self.total_stake
+    }
+
+    fn enter_round(&mut self, round: u32) {
+        let height = self.height();
+        let total = self.total_stake as f64;
+        self.round = round;
+        self.proposal = None;
+        self.prevotes = VoteSet::new(VoteType::Prevote, height, round, total);
+        self.precommits = VoteSet::new(VoteType::Precommit, height, round, total);
+        self.prevoted = false;
+        self.precommitted = false;
+        self.awaiting_precommits = false;
+        let (due, later): (Vec<Message>, Vec<Message>) = std::mem::take(&mut self.future)
+            .into_iter()
+            .partition(|message| position(message) <= Some((height, round)));
+        self.future = later;
+        self.future_voters.retain(|&key, _| key > (height, round));
+        self.inbox.extend(due);
+
+        if self.proposer(round) != self.engine.validator_id() {
+            return;
+        }
+        if let Some(block) = self.valid_block.clone() {
+            let pol = self.valid_pol.clone();
+            self.broadcast(Message::Proposal { round, block, pol });
+        } else {
+            let timestamp = self.head().header.timestamp + BLOCK_INTERVAL_MS;
+            let transactions = self
+                .mempool
+                .select(self.engine.accounts(), self.engine.validator_id());
+            if let Ok(block) = self
+                .engine
+                .propose_block_with_transactions(timestamp, transactions)
+            {
+                self.broadcast(Message::Proposal {
+                    round,
+                    block,
+                    pol: Vec::new(),
+                });
+            }
+        }
+    }
+}
+
+/// Returns the height and round a consensus message belongs to.
+fn position(message: &Message) -> Option<(u64, u32)> {
+    match message {
+        Message::Proposal { round, block, .. } => Some((block.header.height, *round)),
+        Message::Vote(vote) => Some((vote.height, vote.round)),
+        _ => None,
+    }
+}
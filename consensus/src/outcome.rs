@@ -0,0 +1,71 @@
+// src/outcome.rs
+
+use crate::block::Hash;
+use crate::slashing::SlashRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a validator judged the proposal of a round, as shown by its counted prevote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Verdict {
+    /// Prevoted for the block.
+    Accept,
+    /// Prevoted nil, rejecting the block or skipping an offline proposer.
+    Reject,
+    /// Cast no counted prevote, being offline or signing with the wrong key.
+    Absent,
+}
+
+/// What happened in one round of [`run_consensus_round`](crate::Network::run_consensus_round).
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::{Network, PosygDcsEngine, Verdict};
+///
+/// let mut network = Network::from_seed(
+///     vec![
+///         PosygDcsEngine::new("v1".to_string(), 1000, false),
+///         PosygDcsEngine::new("v2".to_string(), 1000, false),
+///     ],
+///     3,
+/// );
+/// let outcome = network.run_consensus_round().unwrap();
+/// assert!(outcome.finalized);
+/// assert_eq!((outcome.height, outcome.round), (1, 0));
+/// assert_eq!(outcome.block_hash, Some(network.head().hash()));
+/// assert!(outcome.verdicts.values().all(|verdict| *verdict == Verdict::Accept));
+/// assert!(outcome.score_delta(&outcome.proposer) > 0.0);
+/// assert!(outcome.violations.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoundOutcome {
+    /// Height the round tried to finalize.
+    pub height: u64,
+    /// Round at that height.
+    pub round: u32,
+    /// Identifier of the proposer.
+    pub proposer: String,
+    /// Hash of the proposed block, or `None` if the proposer was offline and skipped.
+    pub block_hash: Option<Hash>,
+    /// Whether the block was finalized.
+    pub finalized: bool,
+    /// Verdict of every validator on the proposal, by identifier.
+    pub verdicts: BTreeMap<String, Verdict>,
+    /// Change in synergy score of every validator during the round, by identifier.
+    pub score_deltas: BTreeMap<String, f64>,
+    /// Slashings applied during the round, with the identifier of the slashed validator.
+    pub violations: Vec<(String, SlashRecord)>,
+}
+
+impl RoundOutcome {
+    /// Returns whether the proposer was offline and the round was skipped without a proposal.
+    pub fn is_skipped(&self) -> bool {
+        self.block_hash.is_none()
+    }
+
+    /// Returns how much the synergy score of validator `id` changed, or 0 if it is unknown.
+    pub fn score_delta(&self, id: &str) -> f64 {
+        self.score_deltas.get(id).copied().unwrap_or(0.0)
+    }
+}
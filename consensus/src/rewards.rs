@@ -0,0 +1,133 @@
+// src/rewards.rs
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How many tokens the network mints, and when.
+///
+/// Every finalized block mints a reward for its proposer, and every epoch boundary mints a reward
+/// shared among the validators that were active during the epoch. Both rewards halve every
+/// `halving_interval` blocks.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::rewards::IssuanceSchedule;
+///
+/// let schedule = IssuanceSchedule {
+///     block_reward: 100,
+///     epoch_reward: 1000,
+///     halving_interval: 10,
+///     synergy_scaled: false,
+/// };
+/// assert_eq!(schedule.block_reward_at(1), 100);
+/// assert_eq!(schedule.block_reward_at(10), 50);
+/// assert_eq!(schedule.epoch_reward_at(25), 250);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IssuanceSchedule {
+    /// Reward minted for the proposer of every finalized block, before halvings.
+    pub block_reward: u64,
+    /// Reward minted at every epoch boundary and shared among the epoch's validators, before
+    /// halvings.
+    pub epoch_reward: u64,
+    /// Number of blocks between halvings; 0 disables halving.
+    pub halving_interval: u64,
+    /// Whether the epoch reward is shared in proportion to proposer-selection weight, which
+    /// includes the synergy score, instead of stake alone.
+    pub synergy_scaled: bool,
+}
+
+impl Default for IssuanceSchedule {
+    fn default() -> Self {
+        Self {
+            block_reward: 10,
+            epoch_reward: 100,
+            halving_interval: 100_000,
+            synergy_scaled: false,
+        }
+    }
+}
+
+impl IssuanceSchedule {
+    /// Returns the proposer reward for the block at `height`.
+    pub fn block_reward_at(&self, height: u64) -> u64 {
+        self.halved(self.block_reward, height)
+    }
+
+    /// Returns the epoch reward for an epoch ending at `height`.
+    pub fn epoch_reward_at(&self, height: u64) -> u64 {
+        self.halved(self.epoch_reward, height)
+    }
+
+    fn halved(&self, reward: u64, height: u64) -> u64 {
+        if self.halving_interval == 0 {
+            return reward;
+        }
+        let halvings = height / self.halving_interval;
+        if halvings >= u64::BITS as u64 {
+            0
+        } else {
+            reward >> halvings
+        }
+    }
+}
+
+/// The tokens issued to every account, as initial balances or minted rewards, and the total supply
+/// they add up to.
+///
+/// The ledger only records issuance; the tokens themselves are credited to spendable balances in
+/// the [`AccountState`](crate::state::AccountState), where fees and transfers move them between
+/// accounts without changing the supply. Bonded and delegated stake is tracked by the
+/// [`Network`](crate::Network).
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::rewards::Ledger;
+///
+/// let mut ledger = Ledger::new();
+/// ledger.mint("v1", 30);
+/// ledger.mint("alice", 20);
+/// ledger.mint("v1", 5);
+/// assert_eq!(ledger.issued("v1"), 35);
+/// assert_eq!(ledger.issued("bob"), 0);
+/// assert_eq!(ledger.total_supply(), 55);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ledger {
+    issued: BTreeMap<String, u64>,
+    total_supply: u64,
+}
+
+impl Ledger {
+    /// Creates an empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `amount` new tokens issued to `account`.
+    pub fn mint(&mut self, account: &str, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        *self.issued.entry(account.to_string()).or_insert(0) += amount;
+        self.total_supply += amount;
+    }
+
+    /// Returns the number of tokens issued to `account`.
+    pub fn issued(&self, account: &str) -> u64 {
+        self.issued.get(account).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of tokens issued to every account that received any, ordered by account.
+    pub fn issuance(&self) -> &BTreeMap<String, u64> {
+        &self.issued
+    }
+
+    /// Returns the number of tokens issued so far.
+    pub fn total_supply(&self) -> u64 {
+        self.total_supply
+    }
+}
@@ -0,0 +1,542 @@
+// src/rpc.rs
+
+use crate::block::{Block, Hash};
+use crate::node::Node;
+use crate::vote::QuorumCertificate;
+use crate::{ConsensusEngine, ConsensusError, Network};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest request body the API accepts, in bytes.
+pub const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// How long a client has to send its whole request, however it spreads out the bytes.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Threads answering the requests of an HTTP server.
+pub const HTTP_WORKERS: usize = 4;
+
+/// Accepted connections an HTTP server queues for a free worker; further ones wait to be
+/// accepted.
+pub const HTTP_BACKLOG: usize = 16;
+
+/// A block with its hash and the certificate that finalized it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockResponse {
+    /// Hash of the block.
+    pub hash: Hash,
+    /// The block.
+    pub block: Block,
+    /// Certificate that finalized the block; the genesis block has none.
+    pub certificate: Option<QuorumCertificate>,
+}
+
+/// A validator as reported by the API.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStatus {
+    /// Identifier of the validator.
+    pub id: String,
+    /// Stake the validator votes with.
+    pub stake: u64,
+    /// Synergy score of the validator.
+    pub synergy_score: f64,
+    /// Invalid blocks the validator proposed.
+    pub violations: u64,
+    /// Blocks the validator proposed.
+    pub proposed: u64,
+    /// Blocks of the validator that were finalized.
+    pub accepted: u64,
+}
+
+/// Progress of the height being decided.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundState {
+    /// Height being decided.
+    pub height: u64,
+    /// Round of that height.
+    pub round: u32,
+    /// Proposer of the round, if known before it proposes.
+    pub proposer: Option<String>,
+    /// Whether the round's proposal has been received.
+    pub has_proposal: bool,
+    /// Whether the local validator has prevoted in the round.
+    pub prevoted: bool,
+    /// Whether the local validator has precommitted in the round.
+    pub precommitted: bool,
+    /// How long the round waits for its proposal, in milliseconds.
+    pub timeout_ms: u64,
+}
+
+/// The chain data the API serves.
+pub trait ChainView {
+    /// Returns the finalized chain, starting with the genesis block.
+    fn chain(&self) -> &[Block];
+
+    /// Returns the certificate that finalized the block at `height`, if any.
+    fn certificate(&self, height: u64) -> Option<&QuorumCertificate>;
+
+    /// Returns the validators with their scores and proposal counts.
+    fn validator_statuses(&self) -> Vec<ValidatorStatus>;
+
+    /// Returns the progress of the height being decided.
+    fn round_state(&self) -> RoundState;
+}
+
+impl ChainView for Node {
+    fn chain(&self) -> &[Block] {
+        Node::chain(self)
+    }
+
+    fn certificate(&self, height: u64) -> Option<&QuorumCertificate> {
+        Node::certificate(self, height)
+    }
+
+    /// Scores of validators other than the node's own count only the proposal outcomes the node
+    /// has seen; see [`ValidatorRecord`](crate::node::ValidatorRecord).
+    fn validator_statuses(&self) -> Vec<ValidatorStatus> {
+        self.validators()
+            .iter()
+            .map(|v| {
+                let record = self.record(&v.id);
+                ValidatorStatus {
+                    id: v.id.clone(),
+                    stake: v.stake,
+                    synergy_score: record.synergy_score,
+                    violations: record.violations,
+                    proposed: record.proposed,
+                    accepted: record.accepted,
+                }
+            })
+            .collect()
+    }
+
+    fn round_state(&self) -> RoundState {
+        let (prevoted, precommitted) = self.has_voted();
+        RoundState {
+            height: self.height(),
+            round: self.round(),
+            proposer: Some(self.proposer(self.round()).to_string()),
+            has_proposal: self.has_proposal(),
+            prevoted,
+            precommitted,
+            timeout_ms: self.round_timeout_ms(),
+        }
+    }
+}
+
+impl<R: RngCore> ChainView for Network<R> {
+    fn chain(&self) -> &[Block] {
+        Network::chain(self)
+    }
+
+    fn certificate(&self, height: u64) -> Option<&QuorumCertificate> {
+        Network::certificate(self, height)
+    }
+
+    fn validator_statuses(&self) -> Vec<ValidatorStatus> {
+        self.validators
+            .iter()
+            .map(|v| ValidatorStatus {
+                id: v.validator_id().to_string(),
+                stake: v.total_stake(),
+                synergy_score: v.get_synergy_score(),
+                violations: v.violations(),
+                proposed: v.proposed_blocks(),
+                accepted: v.accepted_blocks(),
+            })
+            .collect()
+    }
+
+    /// A network runs each round in one call, so between calls no proposal or vote is pending and
+    /// the next proposer is not drawn yet.
+    fn round_state(&self) -> RoundState {
+        RoundState {
+            height: self.head().header.height + 1,
+            round: self.current_round(),
+            proposer: None,
+            has_proposal: false,
+            prevoted: false,
+            precommitted: false,
+            timeout_ms: self.timeout_config().propose_timeout(self.current_round()),
+        }
+    }
+}
+
+/// A question the API answers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Query {
+    /// The last finalized block.
+    Head,
+    /// The finalized block at a height.
+    BlockByHeight(u64),
+    /// The finalized block with a hash.
+    BlockByHash(Hash),
+    /// The validator set.
+    Validators,
+    /// The progress of the height being decided.
+    Round,
+}
+
+impl Query {
+    /// Parses the path of a `GET` request:
+    ///
+    /// - `/head`
+    /// - `/blocks/<height>`
+    /// - `/blocks/hash/<hash>`
+    /// - `/validators`
+    /// - `/round`
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = path
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+        match segments.as_slice() {
+            ["head"] => Some(Query::Head),
+            ["blocks", "hash", hash] => Hash::from_hex(hash).map(Query::BlockByHash),
+            ["blocks", height] => height.parse().ok().map(Query::BlockByHeight),
+            ["validators"] => Some(Query::Validators),
+            ["round"] => Some(Query::Round),
+            _ => None,
+        }
+    }
+
+    /// Parses a JSON-RPC call: `head`, `block` with a `height` or `hash` parameter, `validators`
+    /// or `round`.
+    pub fn from_json_rpc(method: &str, params: &Value) -> Result<Self, RpcError> {
+        match method {
+            "head" => Ok(Query::Head),
+            "block" => {
+                if let Some(height) = params.get("height").and_then(Value::as_u64) {
+                    Ok(Query::BlockByHeight(height))
+                } else if let Some(hash) = params
+                    .get("hash")
+                    .and_then(Value::as_str)
+                    .and_then(Hash::from_hex)
+                {
+                    Ok(Query::BlockByHash(hash))
+                } else {
+                    Err(RpcError::InvalidParams)
+                }
+            }
+            "validators" => Ok(Query::Validators),
+            "round" => Ok(Query::Round),
+            _ => Err(RpcError::MethodNotFound),
+        }
+    }
+
+    /// Answers the query from `view`, or returns `None` if the block asked for is unknown.
+    pub fn answer(&self, view: &impl ChainView) -> Option<Value> {
+        let block = |block: &Block| BlockResponse {
+            hash: block.hash(),
+            block: block.clone(),
+            certificate: view.certificate(block.header.height).cloned(),
+        };
+        let value = match self {
+            Query::Head => json!(block(view.chain().last()?)),
+            Query::BlockByHeight(height) => {
+                json!(block(view.chain().get(usize::try_from(*height).ok()?)?))
+            }
+            Query::BlockByHash(hash) => {
+                json!(block(view.chain().iter().find(|b| b.hash() == *hash)?))
+            }
+            Query::Validators => json!(view.validator_statuses()),
+            Query::Round => json!(view.round_state()),
+        };
+        Some(value)
+    }
+}
+
+/// An error of a JSON-RPC call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcError {
+    /// The body is not JSON.
+    ParseError,
+    /// The body is not a JSON-RPC request.
+    InvalidRequest,
+    /// The method does not exist.
+    MethodNotFound,
+    /// The parameters do not fit the method.
+    InvalidParams,
+}
+
+impl RpcError {
+    /// Returns the JSON-RPC error code.
+    pub fn code(&self) -> i64 {
+        match self {
+            RpcError::ParseError => -32700,
+            RpcError::InvalidRequest => -32600,
+            RpcError::MethodNotFound => -32601,
+            RpcError::InvalidParams => -32602,
+        }
+    }
+
+    /// Returns the JSON-RPC error message.
+    pub fn message(&self) -> &'static str {
+        match self {
+            RpcError::ParseError => "Parse error",
+            RpcError::InvalidRequest => "Invalid request",
+            RpcError::MethodNotFound => "Method not found",
+            RpcError::InvalidParams => "Invalid params",
+        }
+    }
+}
+
+/// Answers an HTTP request for the chain data of `view`, returning the status code and JSON body.
+///
+/// `GET` requests use the paths of [`Query::from_path`]; a `POST` to `/` is a JSON-RPC 2.0 call
+/// whose result is `null` for an unknown block.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::rpc::handle_request;
+/// use peo_consensus::{Network, PosygDcsEngine};
+/// use serde_json::Value;
+///
+/// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+/// let mut network = Network::from_seed(vec![validator], 1);
+/// network.run_consensus_round().unwrap();
+///
+/// let (status, body) = handle_request(&network, "GET", "/head", b"");
+/// assert_eq!(status, 200);
+/// let head: Value = serde_json::from_str(&body).unwrap();
+/// assert_eq!(head["block"]["header"]["height"], 1);
+///
+/// let call = br#"{"jsonrpc": "2.0", "id": 7, "method": "validators"}"#;
+/// let (status, body) = handle_request(&network, "POST", "/", call);
+/// let response: Value = serde_json::from_str(&body).unwrap();
+/// assert_eq!((status, response["id"].as_u64()), (200, Some(7)));
+/// assert_eq!(response["result"][0]["id"], "v1");
+///
+/// assert_eq!(handle_request(&network, "GET", "/blocks/9", b"").0, 404);
+/// ```
+pub fn handle_request(
+    view: &impl ChainView,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> (u16, String) {
+    match method {
+        "GET" => match Query::from_path(path) {
+            Some(query) => match query.answer(view) {
+                Some(value) => (200, value.to_string()),
+                None => (404, json!({ "error": "block not found" }).to_string()),
+            },
+            None => (404, json!({ "error": "no such endpoint" }).to_string()),
+        },
+        "POST" if path == "/" => (200, json_rpc(view, body).to_string()),
+        _ => (405, json!({ "error": "method not allowed" }).to_string()),
+    }
+}
+
+fn json_rpc(view: &impl ChainView, body: &[u8]) -> Value {
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(_) => return rpc_error(Value::Null, RpcError::ParseError),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let (Some("2.0"), Some(method)) = (
+        request.get("jsonrpc").and_then(Value::as_str),
+        request.get("method").and_then(Value::as_str),
+    ) else {
+        return rpc_error(id, RpcError::InvalidRequest);
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match Query::from_json_rpc(method, &params) {
+        Ok(query) => json!({ "jsonrpc": "2.0", "id": id, "result": query.answer(view) }),
+        Err(error) => rpc_error(id, error),
+    }
+}
+
+fn rpc_error(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code(), "message": error.message() },
+    })
+}
+
+/// An HTTP server answering API requests from a shared [`ChainView`] on background threads.
+///
+/// Every request is served on its own connection, which is closed after the response.
+pub struct RpcServer {
+    local_addr: SocketAddr,
+}
+
+impl RpcServer {
+    /// Listens on `addr` and serves the chain data of `view` until the process exits.
+    pub fn serve<V>(addr: impl ToSocketAddrs, view: Arc<Mutex<V>>) -> Result<Self, ConsensusError>
+    where
+        V: ChainView + Send + 'static,
+    {
+        let local_addr = serve_http(addr, move |request| match request {
+            Some((method, path, body)) => {
+                let view = view.lock().expect("chain view lock poisoned");
+                let (status, body) = handle_request(&*view, method, path, body);
+                (status, "application/json", body)
+            }
+            None => (
+                400,
+                "application/json",
+                json!({ "error": "malformed request" }).to_string(),
+            ),
+        })?;
+        Ok(Self { local_addr })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// The method, path and body of an HTTP request.
+pub(crate) type HttpRequest<'a> = (&'a str, &'a str, &'a [u8]);
+
+/// The status code, content type and body of an HTTP response.
+pub(crate) type HttpResponse = (u16, &'static str, String);
+
+/// Listens on `addr` and answers every HTTP request with the status code, content type and body
+/// returned by `handler`, which is given `None` for a malformed request.
+///
+/// Requests are answered by [`HTTP_WORKERS`] threads, so slow clients cannot make the server
+/// spawn threads without bound. A client has a fixed deadline to send its whole request, so it
+/// cannot hold a worker longer by trickling bytes.
+pub(crate) fn serve_http<F>(
+    addr: impl ToSocketAddrs,
+    handler: F,
+) -> Result<SocketAddr, ConsensusError>
+where
+    F: Fn(Option<HttpRequest>) -> HttpResponse + Send + Sync + 'static,
+{
+    let listener =
+        TcpListener::bind(addr).map_err(|e| ConsensusError::NetworkError(e.to_string()))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| ConsensusError::NetworkError(e.to_string()))?;
+    let handler = Arc::new(handler);
+    let (queue, connections) = mpsc::sync_channel(HTTP_BACKLOG);
+    let connections = Arc::new(Mutex::new(connections));
+    for _ in 0..HTTP_WORKERS {
+        let handler = Arc::clone(&handler);
+        let connections = Arc::clone(&connections);
+        thread::spawn(move || serve_connections(&connections, &*handler));
+    }
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if queue.send(stream).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(local_addr)
+}
+
+/// Answers the connections queued for the workers until the server's listener is gone.
+fn serve_connections(
+    connections: &Mutex<Receiver<TcpStream>>,
+    handler: &dyn Fn(Option<HttpRequest>) -> HttpResponse,
+) {
+    loop {
+        let next = connections
+            .lock()
+            .expect("connection queue lock poisoned")
+            .recv();
+        let Ok(mut stream) = next else {
+            return;
+        };
+        let request = read_request(&mut stream);
+        let response = handler(
+            request
+                .as_ref()
+                .map(|(method, path, body)| (method.as_str(), path.as_str(), body.as_slice())),
+        );
+        write_response(&mut stream, response);
+    }
+}
+
+/// Returns the reason phrase of an HTTP status code.
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Unknown",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, (status, content_type, body): HttpResponse) {
+    let reason = reason_phrase(status);
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+/// Reads from a stream until a deadline, failing with [`io::ErrorKind::TimedOut`] once it has
+/// passed.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Reads the method, path and body of an HTTP/1.1 request, giving up [`REQUEST_TIMEOUT`] after
+/// it starts reading.
+fn read_request(stream: &mut TcpStream) -> Option<(String, String, Vec<u8>)> {
+    let reader = DeadlineReader {
+        stream,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    };
+    let mut reader = BufReader::new(reader.take(MAX_REQUEST_BYTES as u64 * 2));
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = (parts.next()?.to_string(), parts.next()?.to_string());
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some((method, path, body))
+}
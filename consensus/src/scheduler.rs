@@ -0,0 +1,124 @@
+// src/scheduler.rs
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+struct Entry<E> {
+    at_ms: u64,
+    seq: u64,
+    event: E,
+}
+
+impl<E> PartialEq for Entry<E> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at_ms, self.seq) == (other.at_ms, other.seq)
+    }
+}
+
+impl<E> Eq for Entry<E> {}
+
+impl<E> PartialOrd for Entry<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E> Ord for Entry<E> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.at_ms, self.seq).cmp(&(other.at_ms, other.seq))
+    }
+}
+
+/// A discrete-event scheduler: a queue of events ordered by the virtual time they are due at.
+///
+/// Popping an event moves the virtual clock to its time, so simulated time passes only as events
+/// happen and idle stretches cost nothing. Events due at the same time come out in the order they
+/// were scheduled, which keeps a simulation deterministic.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::scheduler::Scheduler;
+///
+/// let mut scheduler = Scheduler::new();
+/// scheduler.schedule_in(200, "second");
+/// scheduler.schedule_in(50, "first");
+/// scheduler.schedule_at(200, "third");
+///
+/// assert_eq!(scheduler.pop(), Some((50, "first")));
+/// scheduler.schedule_in(100, "fourth");
+/// assert_eq!(scheduler.now(), 50);
+/// assert_eq!(scheduler.pop(), Some((150, "fourth")));
+/// assert_eq!(scheduler.pop(), Some((200, "second")));
+/// assert_eq!(scheduler.pop(), Some((200, "third")));
+/// assert_eq!(scheduler.pop(), None);
+/// ```
+pub struct Scheduler<E> {
+    queue: BinaryHeap<Reverse<Entry<E>>>,
+    now_ms: u64,
+    seq: u64,
+}
+
+impl<E> Default for Scheduler<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Scheduler<E> {
+    /// Creates a scheduler without events, with its clock at 0.
+    pub fn new() -> Self {
+        Self {
+            queue: BinaryHeap::new(),
+            now_ms: 0,
+            seq: 0,
+        }
+    }
+
+    /// Returns the virtual time, in milliseconds: the time of the last event popped.
+    pub fn now(&self) -> u64 {
+        self.now_ms
+    }
+
+    /// Returns the number of pending events.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no event is pending.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Schedules `event` at virtual time `at_ms`, or now if that time has passed.
+    pub fn schedule_at(&mut self, at_ms: u64, event: E) {
+        self.seq += 1;
+        self.queue.push(Reverse(Entry {
+            at_ms: at_ms.max(self.now_ms),
+            seq: self.seq,
+            event,
+        }));
+    }
+
+    /// Schedules `event` `delay_ms` milliseconds from now.
+    pub fn schedule_in(&mut self, delay_ms: u64, event: E) {
+        self.schedule_at(self.now_ms.saturating_add(delay_ms), event);
+    }
+
+    /// Returns the time of the next event, if any.
+    pub fn peek_time(&self) -> Option<u64> {
+        self.queue.peek().map(|Reverse(entry)| entry.at_ms)
+    }
+
+    /// Removes the next event and moves the clock to its time.
+    pub fn pop(&mut self) -> Option<(u64, E)> {
+        let Reverse(entry) = self.queue.pop()?;
+        self.now_ms = entry.at_ms;
+        Some((entry.at_ms, entry.event))
+    }
+
+    /// Moves the clock forward to `at_ms` without popping an event; the clock never goes back.
+    pub fn advance_to(&mut self, at_ms: u64) {
+        self.now_ms = self.now_ms.max(at_ms);
+    }
+}
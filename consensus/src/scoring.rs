@@ -0,0 +1,210 @@
+// src/scoring.rs
+
+use crate::ConsensusError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The facts about a proposal outcome that a [`ScoringPolicy`] turns into a score change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreInputs {
+    /// Whether the proposed block was finalized.
+    pub block_accepted: bool,
+    /// The proposer's stake.
+    pub stake: u64,
+    /// Validation contribution accumulated since the proposer's previous score update.
+    pub validation: f64,
+    /// Whether the proposal was a violation.
+    pub violation_occurred: bool,
+    /// Total violations of the proposer, including this one if `violation_occurred`.
+    pub violations: u64,
+}
+
+/// A formula turning consensus outcomes into synergy score changes.
+///
+/// Implementations can be swapped per [`Network`](crate::Network) to compare scoring schemes in
+/// simulation.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::scoring::{ScoreInputs, ScoringPolicy};
+/// use peo_consensus::{ConsensusEngine, Network, PosygDcsEngine};
+/// use std::sync::Arc;
+///
+/// /// Rewards accepted blocks only, ignoring stake.
+/// struct FlatPolicy;
+///
+/// impl ScoringPolicy for FlatPolicy {
+///     fn proposal_delta(&self, inputs: &ScoreInputs) -> f64 {
+///         if inputs.block_accepted { 1.0 } else { -1.0 }
+///     }
+///     fn validation_delta(&self, _validation: f64) -> f64 {
+///         0.0
+///     }
+/// }
+///
+/// let validator = PosygDcsEngine::new("v1".to_string(), 1000, false);
+/// let mut network = Network::from_seed(vec![validator], 1);
+/// network.set_scoring_policy(Arc::new(FlatPolicy));
+/// network.run_consensus_round().unwrap();
+/// assert_eq!(network.validators[0].get_synergy_score(), 1.0);
+/// ```
+pub trait ScoringPolicy: Send + Sync {
+    /// Returns the score change of a proposer for the outcome of its proposal.
+    fn proposal_delta(&self, inputs: &ScoreInputs) -> f64;
+
+    /// Returns the score change of a validator for `validation` contribution earned while
+    /// validating other validators' proposals.
+    fn validation_delta(&self, validation: f64) -> f64;
+
+    /// Returns the score change of a validator for `missed_rounds` rounds in which it was offline.
+    ///
+    /// Downtime costs nothing unless the policy says otherwise.
+    fn downtime_delta(&self, missed_rounds: u64) -> f64 {
+        let _ = missed_rounds;
+        0.0
+    }
+
+    /// Returns a validator's weight in proposer selection from its synergy score and its own and
+    /// delegated stake.
+    ///
+    /// The weight is the score plus 1% of the stake unless the policy says otherwise.
+    fn selection_weight(&self, synergy_score: f64, stake: u64) -> f64 {
+        synergy_score + stake as f64 * 0.01
+    }
+}
+
+/// Parameters of the Dynamic Contribution Scoring formula.
+///
+/// A proposer's score changes by `alpha * h + beta * e + gamma * v - delta * p`, where `h` is 1 for
+/// an accepted block, `e` is `stake_factor * stake`, `v` is the validation contribution and `p` is
+/// `base_penalty * multiplier^(n - 1)` for the proposer's `n`-th violation. A validator also loses
+/// `delta * downtime_penalty` for every round it misses while offline.
+///
+/// Missing fields in a configuration file take their default values.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::scoring::DcsParams;
+///
+/// let params = DcsParams::from_toml_str("alpha = 1.0\nbase_penalty = 20.0").unwrap();
+/// assert_eq!(params.alpha, 1.0);
+/// assert_eq!(params.base_penalty, 20.0);
+/// assert_eq!(params.beta, DcsParams::default().beta);
+///
+/// let params = DcsParams::from_json_str(r#"{ "gamma": 0.5 }"#).unwrap();
+/// assert_eq!(params.gamma, 0.5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DcsParams {
+    /// Weight of block acceptance.
+    pub alpha: f64,
+    /// Weight of economic stake.
+    pub beta: f64,
+    /// Weight of validation contribution.
+    pub gamma: f64,
+    /// Weight of the violation penalty.
+    pub delta: f64,
+    /// Fraction of stake counted as the economic term `e`, and added to the synergy score to weigh
+    /// validators in proposer selection.
+    pub stake_factor: f64,
+    /// Penalty for a validator's first violation.
+    pub base_penalty: f64,
+    /// Factor the penalty grows by with each further violation.
+    pub multiplier: f64,
+    /// Penalty for every round a validator misses while offline.
+    pub downtime_penalty: f64,
+}
+
+impl Default for DcsParams {
+    fn default() -> Self {
+        Self {
+            alpha: 0.4,
+            beta: 0.3,
+            gamma: 0.2,
+            delta: 0.5,
+            stake_factor: 0.01,
+            base_penalty: 10.0,
+            multiplier: 2.0,
+            downtime_penalty: 1.0,
+        }
+    }
+}
+
+impl DcsParams {
+    /// Parses parameters from a TOML document.
+    pub fn from_toml_str(s: &str) -> Result<Self, ConsensusError> {
+        toml::from_str(s).map_err(|e| ConsensusError::InvalidConfig(e.to_string()))
+    }
+
+    /// Parses parameters from a JSON document.
+    pub fn from_json_str(s: &str) -> Result<Self, ConsensusError> {
+        serde_json::from_str(s).map_err(|e| ConsensusError::InvalidConfig(e.to_string()))
+    }
+
+    /// Loads parameters from a `.toml` or `.json` file, chosen by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConsensusError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConsensusError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(ConsensusError::InvalidConfig(format!(
+                "{}: expected a .toml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Returns the penalty `p` for a validator's `violations`-th violation.
+    pub fn penalty(&self, violations: u64) -> f64 {
+        if violations == 0 {
+            return 0.0;
+        }
+        self.base_penalty * self.multiplier.powi(violations as i32 - 1)
+    }
+}
+
+/// The default scoring policy: the Dynamic Contribution Scoring formula.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DcsPolicy {
+    /// Parameters of the formula.
+    pub params: DcsParams,
+}
+
+impl DcsPolicy {
+    /// Creates a policy with the given parameters.
+    pub fn new(params: DcsParams) -> Self {
+        Self { params }
+    }
+}
+
+impl ScoringPolicy for DcsPolicy {
+    fn proposal_delta(&self, inputs: &ScoreInputs) -> f64 {
+        let p = &self.params;
+        let h = if inputs.block_accepted { 1.0 } else { 0.0 };
+        let e = inputs.stake as f64 * p.stake_factor;
+        let v = inputs.validation;
+        let penalty = if inputs.violation_occurred {
+            p.penalty(inputs.violations)
+        } else {
+            0.0
+        };
+        p.alpha * h + p.beta * e + p.gamma * v - p.delta * penalty
+    }
+
+    fn validation_delta(&self, validation: f64) -> f64 {
+        self.params.gamma * validation
+    }
+
+    fn downtime_delta(&self, missed_rounds: u64) -> f64 {
+        -self.params.delta * self.params.downtime_penalty * missed_rounds as f64
+    }
+
+    fn selection_weight(&self, synergy_score: f64, stake: u64) -> f64 {
+        synergy_score + stake as f64 * self.params.stake_factor
+    }
+}
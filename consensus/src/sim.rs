@@ -0,0 +1,589 @@
+// src/sim.rs
+
+use crate::node::{Message, Node, ValidatorInfo};
+use crate::scheduler::Scheduler;
+use crate::timeout::TimeoutConfig;
+use crate::{ConsensusError, PosygDcsEngine};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+
+/// A fault injected into a [`FaultSimulator`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Stops a validator: it neither sends nor receives until restarted, and messages in flight to
+    /// it are lost.
+    Crash(String),
+    /// Brings a crashed validator back with the state it had when it crashed.
+    Restart(String),
+    /// Adds `ms` of latency to every message sent from `from` to `to`.
+    Delay {
+        /// Sending validator.
+        from: String,
+        /// Receiving validator.
+        to: String,
+        /// Extra latency, in milliseconds.
+        ms: u64,
+    },
+    /// Drops every message sent from `from` to `to`.
+    Drop {
+        /// Sending validator.
+        from: String,
+        /// Receiving validator.
+        to: String,
+    },
+    /// Removes any delay or drop from the link from `from` to `to`.
+    Restore {
+        /// Sending validator.
+        from: String,
+        /// Receiving validator.
+        to: String,
+    },
+    /// Splits the validators into groups that cannot reach each other. Validators left out of
+    /// every group form one more group.
+    Partition(Vec<Vec<String>>),
+    /// Ends any partition.
+    Heal,
+}
+
+/// The distribution the latency of each message on a link is drawn from.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::sim::Latency;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut rng = StdRng::seed_from_u64(7);
+/// assert_eq!(Latency::Fixed(40).sample(&mut rng), 40);
+/// let ms = Latency::Uniform { min_ms: 20, max_ms: 80 }.sample(&mut rng);
+/// assert!((20..=80).contains(&ms));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Latency {
+    /// Every message takes the same time.
+    Fixed(u64),
+    /// Latencies are spread evenly between two bounds, inclusive.
+    Uniform {
+        /// Shortest latency, in milliseconds.
+        min_ms: u64,
+        /// Longest latency, in milliseconds.
+        max_ms: u64,
+    },
+    /// Latencies follow a normal distribution, cut off at 0.
+    Normal {
+        /// Mean latency, in milliseconds.
+        mean_ms: f64,
+        /// Standard deviation, in milliseconds.
+        std_dev_ms: f64,
+    },
+}
+
+impl Latency {
+    /// Draws the latency of one message, in milliseconds.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        match *self {
+            Latency::Fixed(ms) => ms,
+            Latency::Uniform { min_ms, max_ms } => rng.gen_range(min_ms..=max_ms.max(min_ms)),
+            Latency::Normal {
+                mean_ms,
+                std_dev_ms,
+            } => {
+                // Box-Muller transform of two uniform draws.
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                (mean_ms + std_dev_ms * z).max(0.0).round() as u64
+            }
+        }
+    }
+}
+
+/// Latency and bandwidth of a simulated link.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkConfig {
+    /// Distribution of the time a message takes to cross the link once sent.
+    pub latency: Latency,
+    /// Bytes the link transmits per millisecond, or `None` for unlimited bandwidth. Messages on a
+    /// link are sent one after another, each taking its encoded size divided by the bandwidth.
+    pub bandwidth_bytes_per_ms: Option<u64>,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            latency: Latency::Fixed(50),
+            bandwidth_bytes_per_ms: None,
+        }
+    }
+}
+
+/// Timing of a [`FaultSimulator`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimConfig {
+    /// Latency and bandwidth of every link without its own [`LinkConfig`].
+    pub link: LinkConfig,
+    /// Round timeouts of every validator.
+    pub timeouts: TimeoutConfig,
+    /// How often every validator asks its peers for the certified blocks it may have missed, in
+    /// milliseconds.
+    pub sync_interval_ms: u64,
+    /// Seed of the random number generator latencies are drawn from.
+    pub seed: u64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            link: LinkConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            sync_interval_ms: 1_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Safety, liveness and timing of a simulation run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimReport {
+    /// Simulated time, in milliseconds.
+    pub duration_ms: u64,
+    /// Height of every validator's last committed block, by validator.
+    pub heights: BTreeMap<String, u64>,
+    /// Highest height committed by any validator.
+    pub finalized_height: u64,
+    /// Heights at which two validators committed different blocks. Empty unless safety was
+    /// violated.
+    pub conflicts: Vec<u64>,
+    /// Rounds it took to finalize the blocks up to `finalized_height`.
+    pub rounds: u64,
+    /// Finalized blocks per round: 1.0 when no round was ever skipped.
+    pub blocks_per_round: f64,
+    /// Simulated time at which each height, from 1, was first committed by any validator.
+    pub commit_times_ms: Vec<u64>,
+    /// Finalized blocks proposed by each validator.
+    pub proposals: BTreeMap<String, u64>,
+    /// Messages delivered.
+    pub delivered: u64,
+    /// Messages lost to crashes, drops and partitions.
+    pub dropped: u64,
+}
+
+impl SimReport {
+    /// Returns `true` if no two validators committed different blocks at the same height.
+    pub fn is_safe(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Returns the mean time between finalized blocks, in milliseconds, or 0 before the first.
+    pub fn mean_block_time_ms(&self) -> f64 {
+        match self.commit_times_ms.last() {
+            Some(&last) => last as f64 / self.commit_times_ms.len() as f64,
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Link {
+    Delayed(u64),
+    Dropped,
+}
+
+enum Event {
+    Fault(Fault),
+    Deliver {
+        from: usize,
+        to: usize,
+        message: Box<Message>,
+    },
+    Timeout {
+        node: usize,
+        timer: u64,
+    },
+    Sync,
+}
+
+/// Runs validators as separate [`Node`]s exchanging messages over simulated links, while faults
+/// are injected on a schedule.
+///
+/// Time is virtual and driven by a discrete-event [`Scheduler`]: messages arrive after a latency
+/// drawn from their link's distribution, queued behind earlier messages on links of limited
+/// bandwidth, and nodes time out rounds that make no progress, so thousands of rounds take
+/// seconds. All randomness comes from the configured seed, so a run can be replayed exactly.
+/// Every node regularly sends its peers a [`Message::SyncRequest`], which peers ahead of it answer
+/// with their certified blocks, so a node that fell behind after a crash or a partition catches
+/// up; requests and responses cross the same links, and count in the same statistics, as every
+/// other message. Crashed validators keep their state and resume with it on restart.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::dev_signing_key;
+/// use peo_consensus::sim::{Fault, FaultSimulator, SimConfig};
+/// use peo_consensus::PosygDcsEngine;
+///
+/// let validators = ["v1", "v2", "v3", "v4"]
+///     .iter()
+///     .map(|id| {
+///         PosygDcsEngine::with_signing_key(id.to_string(), 1000, false, dev_signing_key(id))
+///     })
+///     .collect();
+/// let mut sim = FaultSimulator::new(validators, SimConfig::default()).unwrap();
+/// sim.schedule(1_000, Fault::Crash("v4".to_string()));
+/// sim.schedule(20_000, Fault::Restart("v4".to_string()));
+///
+/// let report = sim.run(30_000);
+/// assert!(report.is_safe());
+/// assert!(report.finalized_height > 10);
+/// assert!(report.heights["v4"] + 2 >= report.finalized_height);
+/// ```
+pub struct FaultSimulator {
+    config: SimConfig,
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    alive: Vec<bool>,
+    groups: Option<Vec<usize>>,
+    links: HashMap<(usize, usize), Link>,
+    link_configs: HashMap<(usize, usize), LinkConfig>,
+    busy_until: HashMap<(usize, usize), u64>,
+    scheduler: Scheduler<Event>,
+    rng: StdRng,
+    positions: Vec<(u64, u32, bool)>,
+    timers: Vec<u64>,
+    commit_times_ms: Vec<u64>,
+    started: bool,
+    delivered: u64,
+    dropped: u64,
+}
+
+impl FaultSimulator {
+    /// Creates a simulation of `validators`, each running its own node with the others' keys and
+    /// stakes.
+    ///
+    /// Fails if the validator set is empty, has no stake or repeats an identifier.
+    pub fn new(validators: Vec<PosygDcsEngine>, config: SimConfig) -> Result<Self, ConsensusError> {
+        let set: Vec<ValidatorInfo> = validators
+            .iter()
+            .map(|engine| ValidatorInfo {
+                id: engine.validator_id().to_string(),
+                stake: engine.total_stake(),
+                key: engine.verifying_key(),
+            })
+            .collect();
+        let mut nodes = Vec::with_capacity(validators.len());
+        for engine in validators {
+            let mut node = Node::new(engine, set.clone())?;
+            node.set_timeout_config(config.timeouts);
+            nodes.push(node);
+        }
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.validator_id().to_string(), i))
+            .collect();
+        let count = nodes.len();
+        Ok(Self {
+            config,
+            nodes,
+            index,
+            alive: vec![true; count],
+            groups: None,
+            links: HashMap::new(),
+            link_configs: HashMap::new(),
+            busy_until: HashMap::new(),
+            scheduler: Scheduler::new(),
+            rng: StdRng::seed_from_u64(config.seed),
+            positions: vec![(0, 0, false); count],
+            timers: vec![0; count],
+            commit_times_ms: Vec::new(),
+            started: false,
+            delivered: 0,
+            dropped: 0,
+        })
+    }
+
+    /// Injects `fault` once the simulated clock reaches `at_ms`.
+    ///
+    /// Faults scheduled for the same time are injected in the order they were scheduled.
+    pub fn schedule(&mut self, at_ms: u64, fault: Fault) {
+        self.scheduler.schedule_at(at_ms, Event::Fault(fault));
+    }
+
+    /// Gives the link from `from` to `to` its own latency and bandwidth.
+    pub fn set_link(
+        &mut self,
+        from: &str,
+        to: &str,
+        link: LinkConfig,
+    ) -> Result<(), ConsensusError> {
+        let from = *self
+            .index
+            .get(from)
+            .ok_or(ConsensusError::UnknownValidator)?;
+        let to = *self.index.get(to).ok_or(ConsensusError::UnknownValidator)?;
+        self.link_configs.insert((from, to), link);
+        Ok(())
+    }
+
+    /// Returns the simulated time, in milliseconds.
+    pub fn clock_ms(&self) -> u64 {
+        self.scheduler.now()
+    }
+
+    /// Returns the nodes, in the order their validators were given.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node of validator `id`.
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.index.get(id).map(|&i| &self.nodes[i])
+    }
+
+    /// Runs the simulation for `duration_ms` more milliseconds of simulated time and reports on
+    /// the whole run so far.
+    pub fn run(&mut self, duration_ms: u64) -> SimReport {
+        if !self.started {
+            self.started = true;
+            self.scheduler
+                .schedule_in(self.config.sync_interval_ms, Event::Sync);
+            for i in 0..self.nodes.len() {
+                let messages = self.nodes[i].start();
+                self.after(i, messages);
+                self.reset_timer(i);
+            }
+        }
+        let end = self.clock_ms().saturating_add(duration_ms);
+        while self.scheduler.peek_time().is_some_and(|at| at <= end) {
+            let (_, event) = self.scheduler.pop().expect("peeked above");
+            self.process(event);
+        }
+        self.scheduler.advance_to(end);
+        self.report()
+    }
+
+    /// Returns the safety, liveness and timing of the run so far.
+    pub fn report(&self) -> SimReport {
+        let mut conflicts = Vec::new();
+        let longest = self
+            .nodes
+            .iter()
+            .max_by_key(|node| node.chain().len())
+            .expect("at least one node");
+        for (height, block) in longest.chain().iter().enumerate() {
+            let hash = block.hash();
+            let differs = |node: &Node| node.chain().get(height).is_some_and(|b| b.hash() != hash);
+            if self.nodes.iter().any(differs) {
+                conflicts.push(height as u64);
+            }
+        }
+        let finalized_height = longest.head().header.height;
+        let rounds: u64 = (1..=finalized_height)
+            .map(|height| {
+                longest
+                    .certificate(height)
+                    .map_or(1, |c| c.round as u64 + 1)
+            })
+            .sum();
+        let mut proposals: BTreeMap<String, u64> = self
+            .nodes
+            .iter()
+            .map(|node| (node.validator_id().to_string(), 0))
+            .collect();
+        for block in &longest.chain()[1..] {
+            *proposals.entry(block.header.proposer.clone()).or_insert(0) += 1;
+        }
+        SimReport {
+            duration_ms: self.clock_ms(),
+            heights: self
+                .nodes
+                .iter()
+                .map(|node| (node.validator_id().to_string(), node.head().header.height))
+                .collect(),
+            finalized_height,
+            conflicts,
+            rounds,
+            blocks_per_round: if rounds == 0 {
+                0.0
+            } else {
+                finalized_height as f64 / rounds as f64
+            },
+            commit_times_ms: self.commit_times_ms.clone(),
+            proposals,
+            delivered: self.delivered,
+            dropped: self.dropped,
+        }
+    }
+
+    fn process(&mut self, event: Event) {
+        match event {
+            Event::Fault(fault) => self.inject(fault),
+            Event::Deliver { from, to, message } => {
+                if !self.alive[to] {
+                    self.dropped += 1;
+                    return;
+                }
+                self.delivered += 1;
+                if let Message::SyncRequest { height } = *message {
+                    if let Some(response) = self.nodes[to].sync_response(height) {
+                        self.transmit(to, from, response);
+                    }
+                    return;
+                }
+                let messages = self.nodes[to].handle(*message);
+                self.after(to, messages);
+            }
+            Event::Timeout { node, timer } => {
+                if !self.alive[node] || self.timers[node] != timer {
+                    return;
+                }
+                let messages = self.nodes[node].timeout();
+                self.after(node, messages);
+                if self.timers[node] == timer {
+                    // Still stuck in the same round: time out again, resending the node's votes.
+                    self.reset_timer(node);
+                }
+            }
+            Event::Sync => {
+                self.sync();
+                self.scheduler
+                    .schedule_in(self.config.sync_interval_ms, Event::Sync);
+            }
+        }
+    }
+
+    /// Sends the messages node `i` produced and restarts its round timer if it moved on, or started
+    /// waiting for the round's last precommits.
+    fn after(&mut self, i: usize, messages: Vec<Message>) {
+        self.send(i, messages);
+        let node = &self.nodes[i];
+        let position = (node.height(), node.round(), node.awaiting_precommits());
+        if position == self.positions[i] {
+            return;
+        }
+        self.positions[i] = position;
+        while (self.commit_times_ms.len() as u64) < self.nodes[i].head().header.height {
+            self.commit_times_ms.push(self.scheduler.now());
+        }
+        self.reset_timer(i);
+    }
+
+    fn reset_timer(&mut self, i: usize) {
+        self.timers[i] += 1;
+        let event = Event::Timeout {
+            node: i,
+            timer: self.timers[i],
+        };
+        self.scheduler
+            .schedule_in(self.nodes[i].round_timeout_ms(), event);
+    }
+
+    fn reachable(&self, from: usize, to: usize) -> bool {
+        self.alive[from]
+            && self.alive[to]
+            && self
+                .groups
+                .as_ref()
+                .is_none_or(|groups| groups[from] == groups[to])
+            && self.links.get(&(from, to)) != Some(&Link::Dropped)
+    }
+
+    fn send(&mut self, from: usize, messages: Vec<Message>) {
+        for message in messages {
+            for to in 0..self.nodes.len() {
+                if to != from {
+                    self.transmit(from, to, message.clone());
+                }
+            }
+        }
+    }
+
+    /// Puts `message` on the link from `from` to `to`, or drops it if `to` cannot be reached.
+    fn transmit(&mut self, from: usize, to: usize, message: Message) {
+        if !self.reachable(from, to) {
+            self.dropped += 1;
+            return;
+        }
+        let size = serde_json::to_vec(&message).map_or(0, |bytes| bytes.len() as u64);
+        let link = self
+            .link_configs
+            .get(&(from, to))
+            .copied()
+            .unwrap_or(self.config.link);
+        let now = self.scheduler.now();
+        let mut sent = now;
+        if let Some(bandwidth) = link.bandwidth_bytes_per_ms.filter(|&b| b > 0) {
+            let busy_until = self.busy_until.entry((from, to)).or_insert(0);
+            sent = now.max(*busy_until) + size.div_ceil(bandwidth);
+            *busy_until = sent;
+        }
+        let delay = match self.links.get(&(from, to)) {
+            Some(Link::Delayed(ms)) => *ms,
+            _ => 0,
+        };
+        let deliver_at = sent + link.latency.sample(&mut self.rng) + delay;
+        self.scheduler.schedule_at(
+            deliver_at,
+            Event::Deliver {
+                from,
+                to,
+                message: Box::new(message),
+            },
+        );
+    }
+
+    fn inject(&mut self, fault: Fault) {
+        let index = &self.index;
+        let id = |id: &str| index.get(id).copied();
+        match fault {
+            Fault::Crash(v) => {
+                if let Some(i) = id(&v) {
+                    self.alive[i] = false;
+                }
+            }
+            Fault::Restart(v) => {
+                if let Some(i) = id(&v) {
+                    self.alive[i] = true;
+                    self.reset_timer(i);
+                }
+            }
+            Fault::Delay { from, to, ms } => {
+                if let (Some(from), Some(to)) = (id(&from), id(&to)) {
+                    self.links.insert((from, to), Link::Delayed(ms));
+                }
+            }
+            Fault::Drop { from, to } => {
+                if let (Some(from), Some(to)) = (id(&from), id(&to)) {
+                    self.links.insert((from, to), Link::Dropped);
+                }
+            }
+            Fault::Restore { from, to } => {
+                if let (Some(from), Some(to)) = (id(&from), id(&to)) {
+                    self.links.remove(&(from, to));
+                }
+            }
+            Fault::Partition(partition) => {
+                let mut groups = vec![partition.len(); self.nodes.len()];
+                for (group, members) in partition.iter().enumerate() {
+                    for member in members.iter().filter_map(|member| id(member)) {
+                        groups[member] = group;
+                    }
+                }
+                self.groups = Some(groups);
+            }
+            Fault::Heal => self.groups = None,
+        }
+    }
+
+    /// Has every running node ask its peers for the blocks it may have missed.
+    fn sync(&mut self) {
+        for i in 0..self.nodes.len() {
+            if self.alive[i] {
+                let request = self.nodes[i].sync_request();
+                self.send(i, vec![request]);
+            }
+        }
+    }
+}
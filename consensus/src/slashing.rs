@@ -0,0 +1,128 @@
+// src/slashing.rs
+
+use serde::{Deserialize, Serialize};
+
+/// A provable violation that costs a validator part of its stake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SlashReason {
+    /// The validator proposed a block that the network rejected as invalid.
+    InvalidBlock,
+    /// The validator signed two conflicting messages at the same height and round.
+    Equivocation,
+    /// The validator failed to take part in consensus when it was expected to.
+    Downtime,
+}
+
+/// What happens to slashed stake.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlashDestination {
+    /// Slashed stake is destroyed.
+    #[default]
+    Burn,
+    /// Slashed stake is shared among the remaining validators in proportion to their stake.
+    Redistribute,
+}
+
+/// How much stake each kind of violation costs, and where it goes.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::slashing::{SlashReason, SlashingParams};
+///
+/// let params = SlashingParams::default();
+/// assert_eq!(params.fraction(SlashReason::InvalidBlock), 0.01);
+/// assert_eq!(params.penalty(SlashReason::Equivocation, 1000), 50);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlashingParams {
+    /// Fraction of stake slashed for proposing an invalid block.
+    pub invalid_block_fraction: f64,
+    /// Fraction of stake slashed for equivocating.
+    pub equivocation_fraction: f64,
+    /// Fraction of stake slashed for downtime.
+    pub downtime_fraction: f64,
+    /// Whether slashed stake is burned or redistributed.
+    pub destination: SlashDestination,
+}
+
+impl Default for SlashingParams {
+    fn default() -> Self {
+        Self {
+            invalid_block_fraction: 0.01,
+            equivocation_fraction: 0.05,
+            downtime_fraction: 0.001,
+            destination: SlashDestination::Burn,
+        }
+    }
+}
+
+impl SlashingParams {
+    /// Returns the fraction of stake slashed for `reason`, clamped to `[0, 1]`.
+    pub fn fraction(&self, reason: SlashReason) -> f64 {
+        let fraction = match reason {
+            SlashReason::InvalidBlock => self.invalid_block_fraction,
+            SlashReason::Equivocation => self.equivocation_fraction,
+            SlashReason::Downtime => self.downtime_fraction,
+        };
+        fraction.clamp(0.0, 1.0)
+    }
+
+    /// Returns the amount slashed from `stake` for `reason`, rounded down.
+    pub fn penalty(&self, reason: SlashReason, stake: u64) -> u64 {
+        (stake as f64 * self.fraction(reason)).floor() as u64
+    }
+}
+
+/// A single slashing applied to a validator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashRecord {
+    /// Height at which the violation was punished.
+    pub height: u64,
+    /// The violation that was punished.
+    pub reason: SlashReason,
+    /// Amount of stake taken from the validator's own stake.
+    pub amount: u64,
+    /// Amount of stake taken from the validator's delegators.
+    pub delegated_amount: u64,
+}
+
+/// Splits `amount` among `stakes` in proportion to each stake.
+///
+/// Shares are rounded down and the remainder goes to the largest stakes first, so the shares always
+/// add up to `amount`. If every stake is zero, nothing is distributed.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::slashing::distribute_pro_rata;
+///
+/// assert_eq!(distribute_pro_rata(10, &[1000, 1000, 1000]), vec![4, 3, 3]);
+/// assert_eq!(distribute_pro_rata(10, &[0, 0]), vec![0, 0]);
+/// ```
+pub fn distribute_pro_rata(amount: u64, stakes: &[u64]) -> Vec<u64> {
+    let total: u128 = stakes.iter().map(|&s| s as u128).sum();
+    if total == 0 {
+        return vec![0; stakes.len()];
+    }
+
+    let mut shares: Vec<u64> = stakes
+        .iter()
+        .map(|&s| (amount as u128 * s as u128 / total) as u64)
+        .collect();
+    let mut remainder = amount - shares.iter().sum::<u64>();
+
+    let mut order: Vec<usize> = (0..stakes.len()).collect();
+    order.sort_by(|&a, &b| stakes[b].cmp(&stakes[a]).then(a.cmp(&b)));
+    for i in order.into_iter().cycle() {
+        if remainder == 0 {
+            break;
+        }
+        if stakes[i] > 0 {
+            shares[i] += 1;
+            remainder -= 1;
+        }
+    }
+    shares
+}
@@ -0,0 +1,67 @@
+// src/staking.rs
+
+use crate::PosygDcsEngine;
+
+/// Number of blocks in an epoch unless configured otherwise.
+pub const DEFAULT_EPOCH_LENGTH: u64 = 100;
+
+/// Epochs bonded stake stays locked after a validator leaves, unless configured otherwise.
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 2;
+
+/// A validator that left the active set and whose stake is still locked.
+///
+/// Unbonding stake can still be slashed for violations committed while the validator was active.
+pub struct UnbondingValidator {
+    /// The departed validator, with its stake and history.
+    pub engine: PosygDcsEngine,
+    /// Epoch at whose start the stake is released.
+    pub release_epoch: u64,
+}
+
+/// Stake a delegator withdrew from an active validator and that is still locked.
+///
+/// Like an unbonding validator's stake, it can still be slashed for violations of the validator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnbondingDelegation {
+    /// Account that owns the stake.
+    pub delegator: String,
+    /// Validator the stake was delegated to.
+    pub validator: String,
+    /// Amount of stake still locked.
+    pub amount: u64,
+    /// Epoch at whose start the stake is released.
+    pub release_epoch: u64,
+}
+
+/// Stake returned to its owner at the end of its unbonding period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReleasedStake {
+    /// Identifier of the validator the stake was bonded to.
+    pub validator: String,
+    /// The delegator the stake is returned to, or `None` for the validator's own stake.
+    pub delegator: Option<String>,
+    /// Amount of stake released.
+    pub amount: u64,
+    /// Epoch in which the stake was released.
+    pub epoch: u64,
+}
+
+/// The validator sets of one epoch.
+///
+/// The snapshot of the current epoch is kept up to date as validators bond and unbond; once the
+/// epoch ends its snapshot no longer changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochSnapshot {
+    /// The epoch number, starting at 0 for genesis.
+    pub epoch: u64,
+    /// Height of the first block of the epoch.
+    pub start_height: u64,
+    /// Validators taking part in consensus during the epoch.
+    pub active: Vec<String>,
+    /// Validators that bonded stake during the epoch and join at its end.
+    pub pending: Vec<String>,
+    /// Active validators that asked to unbond during the epoch and leave at its end.
+    pub leaving: Vec<String>,
+    /// Validators whose stake is locked while they unbond.
+    pub unbonding: Vec<String>,
+}
@@ -0,0 +1,277 @@
+// src/state.rs
+
+use crate::block::{Block, Hash};
+use crate::crypto::VerifyingKey;
+use crate::delegation::DelegationBook;
+use crate::transaction::{Transaction, TransactionKind};
+use crate::ConsensusError;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The state of one account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    /// Key the account's transactions must be signed with; an account without one can only
+    /// receive.
+    pub key: Option<VerifyingKey>,
+    /// Spendable balance.
+    pub balance: u64,
+    /// Number of transactions from the account applied so far.
+    pub nonce: u64,
+}
+
+/// Balances, nonces and delegated stake of every account, updated by applying transactions.
+///
+/// Stake bonded with [`TransactionKind::Stake`] leaves the sender's balance and is delegated to a
+/// validator in the state's [`DelegationBook`] until a [`TransactionKind::Unstake`] returns it.
+/// The book is the one the [`Network`](crate::Network) weighs validators, shares rewards and
+/// slashes by, and only validators added with [`add_validator`](Self::add_validator) accept
+/// stake.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::SigningKey;
+/// use peo_consensus::state::AccountState;
+/// use peo_consensus::transaction::{Transaction, TransactionKind};
+///
+/// let key = SigningKey::from_bytes(&[1; 32]);
+/// let mut state = AccountState::new();
+/// state.open("alice".to_string(), key.verifying_key(), 100);
+/// state.add_validator("v1".to_string());
+///
+/// let transfer = |nonce, amount, fee| {
+///     let kind = TransactionKind::Transfer { to: "bob".to_string(), amount };
+///     let mut tx = Transaction::new("alice".to_string(), nonce, kind, fee);
+///     tx.sign(&key);
+///     tx
+/// };
+/// let tx = transfer(0, 60, 5);
+/// state.apply(&tx, "v1").unwrap();
+/// assert_eq!((state.balance("alice"), state.balance("bob"), state.balance("v1")), (35, 60, 5));
+///
+/// // The same transaction cannot be applied twice, and alice cannot overspend.
+/// assert!(state.apply(&tx, "v1").is_err());
+/// assert!(state.check(&transfer(1, 35, 1)).is_err());
+/// assert!(state.check(&transfer(1, 34, 1)).is_ok());
+///
+/// // Alice bonds stake behind v1, and can unbond no more than she bonded.
+/// let bond = |nonce, kind| {
+///     let mut tx = Transaction::new("alice".to_string(), nonce, kind, 0);
+///     tx.sign(&key);
+///     tx
+/// };
+/// let stake = TransactionKind::Stake { validator: "v1".to_string(), amount: 30 };
+/// state.apply(&bond(1, stake), "v1").unwrap();
+/// assert_eq!((state.balance("alice"), state.bonded("alice", "v1")), (5, 30));
+/// let unstake = |amount| TransactionKind::Unstake { validator: "v1".to_string(), amount };
+/// assert!(state.check(&bond(2, unstake(31))).is_err());
+/// state.apply(&bond(2, unstake(10)), "v1").unwrap();
+/// assert_eq!((state.balance("alice"), state.bonded("alice", "v1")), (15, 20));
+///
+/// // Only validators accept stake.
+/// let stray = TransactionKind::Stake { validator: "v9".to_string(), amount: 1 };
+/// assert!(state.check(&bond(3, stray)).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountState {
+    accounts: BTreeMap<String, Account>,
+    delegations: DelegationBook,
+    validators: BTreeSet<String>,
+}
+
+impl AccountState {
+    /// Creates a state without any account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the key of account `id` and credits it `balance`, creating the account if needed.
+    pub fn open(&mut self, id: String, key: VerifyingKey, balance: u64) {
+        let account = self.accounts.entry(id).or_default();
+        account.key = Some(key);
+        account.balance = account.balance.saturating_add(balance);
+    }
+
+    /// Returns account `id`, if it exists.
+    pub fn get(&self, id: &str) -> Option<&Account> {
+        self.accounts.get(id)
+    }
+
+    /// Returns every account, ordered by identifier.
+    pub fn accounts(&self) -> &BTreeMap<String, Account> {
+        &self.accounts
+    }
+
+    /// Returns the spendable balance of account `id`.
+    pub fn balance(&self, id: &str) -> u64 {
+        self.get(id).map_or(0, |account| account.balance)
+    }
+
+    /// Returns the nonce the next transaction from account `id` must carry.
+    pub fn nonce(&self, id: &str) -> u64 {
+        self.get(id).map_or(0, |account| account.nonce)
+    }
+
+    /// Returns the stake account `delegator` has bonded behind `validator`.
+    pub fn bonded(&self, delegator: &str, validator: &str) -> u64 {
+        self.delegations.delegated(delegator, validator)
+    }
+
+    /// Returns the stake every account has delegated, by validator and account.
+    pub fn delegations(&self) -> &DelegationBook {
+        &self.delegations
+    }
+
+    /// Returns the delegations for the network to change outside of transactions.
+    pub(crate) fn delegations_mut(&mut self) -> &mut DelegationBook {
+        &mut self.delegations
+    }
+
+    /// Lets validator `id` accept stake.
+    pub fn add_validator(&mut self, id: String) {
+        self.validators.insert(id);
+    }
+
+    /// Stops validator `id` from accepting stake; stake already delegated to it stays.
+    pub fn remove_validator(&mut self, id: &str) {
+        self.validators.remove(id);
+    }
+
+    /// Returns whether validator `id` accepts stake.
+    pub fn is_validator(&self, id: &str) -> bool {
+        self.validators.contains(id)
+    }
+
+    /// Returns the hash of the canonical encoding of every account and delegation, which block
+    /// headers carry as their state root.
+    ///
+    /// Accounts are encoded in identifier order: the length-prefixed identifier, a flag and the
+    /// key if the account has one, then the balance and nonce as big-endian integers. Delegations
+    /// follow in validator and then account order: the length-prefixed validator and account
+    /// identifiers and the delegated amount.
+    pub fn root(&self) -> Hash {
+        let mut out = Vec::with_capacity(self.accounts.len() * 64);
+        for (id, account) in &self.accounts {
+            out.extend_from_slice(&(id.len() as u32).to_be_bytes());
+            out.extend_from_slice(id.as_bytes());
+            match &account.key {
+                Some(key) => {
+                    out.push(1);
+                    out.extend_from_slice(key.as_bytes());
+                }
+                None => out.push(0),
+            }
+            out.extend_from_slice(&account.balance.to_be_bytes());
+            out.extend_from_slice(&account.nonce.to_be_bytes());
+        }
+        for bond in self.delegations.all() {
+            for id in [&bond.validator, &bond.delegator] {
+                out.extend_from_slice(&(id.len() as u32).to_be_bytes());
+                out.extend_from_slice(id.as_bytes());
+            }
+            out.extend_from_slice(&bond.amount.to_be_bytes());
+        }
+        Hash::digest(&out)
+    }
+
+    /// Checks that `tx` is signed by its sender's key and can be applied next.
+    ///
+    /// On top of [`Transaction::check_stateless`], the sender must have a key the signature
+    /// verifies with, the nonce must be the sender's next one, the balance must cover the fee and
+    /// the amount, a stake or unstake must name a validator that accepts stake, failing with
+    /// [`ConsensusError::UnknownValidator`] otherwise, and an unstake must not exceed the stake
+    /// bonded behind the validator.
+    pub fn check(&self, tx: &Transaction) -> Result<(), ConsensusError> {
+        self.check_admissible(tx)?;
+        let account = &self.accounts[&tx.sender];
+        if tx.nonce != account.nonce {
+            return Err(ConsensusError::InvalidNonce);
+        }
+        let cost = tx
+            .fee
+            .checked_add(tx.kind.debit())
+            .ok_or(ConsensusError::InsufficientFunds)?;
+        if account.balance < cost {
+            return Err(ConsensusError::InsufficientFunds);
+        }
+        match &tx.kind {
+            TransactionKind::Stake { validator, .. }
+            | TransactionKind::Unstake { validator, .. }
+                if !self.is_validator(validator) =>
+            {
+                return Err(ConsensusError::UnknownValidator);
+            }
+            TransactionKind::Unstake { validator, amount }
+                if self.bonded(&tx.sender, validator) < *amount =>
+            {
+                return Err(ConsensusError::InvalidStake);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Checks what cannot change before `tx` is due: it passes the stateless checks, is signed by
+    /// its sender's key and carries a nonce the sender has not used yet.
+    ///
+    /// A transaction admitted to a mempool may still fail [`check`](Self::check) when it comes up,
+    /// for instance if its sender has spent its balance in the meantime.
+    pub fn check_admissible(&self, tx: &Transaction) -> Result<(), ConsensusError> {
+        tx.check_stateless()?;
+        let account = self.get(&tx.sender).ok_or(ConsensusError::UnknownAccount)?;
+        tx.verify_signature(account.key.as_ref().ok_or(ConsensusError::UnknownAccount)?)?;
+        if tx.nonce < account.nonce {
+            return Err(ConsensusError::InvalidNonce);
+        }
+        Ok(())
+    }
+
+    /// Checks and applies `tx`, paying its fee to account `proposer`.
+    ///
+    /// An EVM call only moves its value to the contract account; contract code is not executed.
+    pub fn apply(&mut self, tx: &Transaction, proposer: &str) -> Result<(), ConsensusError> {
+        self.check(tx)?;
+        let sender = self.accounts.get_mut(&tx.sender).expect("checked above");
+        sender.nonce += 1;
+        sender.balance -= tx.fee + tx.kind.debit();
+        match &tx.kind {
+            TransactionKind::Transfer { to, amount } => self.credit(to, *amount),
+            TransactionKind::Stake { validator, amount } => self
+                .delegations
+                .delegate(&tx.sender, validator, *amount)
+                .expect("stateless checks reject empty stakes"),
+            TransactionKind::Unstake { validator, amount } => {
+                self.delegations
+                    .undelegate(&tx.sender, validator, *amount)
+                    .expect("checked above");
+                self.credit(&tx.sender, *amount);
+            }
+            TransactionKind::EvmCall {
+                contract, value, ..
+            } => self.credit(contract, *value),
+        }
+        self.credit(proposer, tx.fee);
+        Ok(())
+    }
+
+    /// Applies the transactions of a finalized block in order, returning how many were applied.
+    ///
+    /// Transactions that fail their checks are skipped, so every validator that applies the same
+    /// block reaches the same state.
+    pub fn apply_block(&mut self, block: &Block) -> usize {
+        block
+            .transactions
+            .iter()
+            .filter(|tx| self.apply(tx, &block.header.proposer).is_ok())
+            .count()
+    }
+
+    /// Adds `amount` to the balance of account `id`, creating the account if needed.
+    pub fn credit(&mut self, id: &str, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        let account = self.accounts.entry(id.to_string()).or_default();
+        account.balance = account.balance.saturating_add(amount);
+    }
+}
@@ -0,0 +1,77 @@
+// src/timeout.rs
+
+use serde::{Deserialize, Serialize};
+
+/// How long validators wait for a proposal before voting to skip the round's proposer, and for
+/// the last precommits of a round once more than two thirds of the stake has precommitted without
+/// agreeing.
+///
+/// Both timeouts grow with the round number, so that after a run of skipped rounds a slow but live
+/// proposer eventually gets enough time to be heard, and each stops growing at its own cap.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::timeout::TimeoutConfig;
+///
+/// let config = TimeoutConfig {
+///     propose_ms: 3_000,
+///     propose_delta_ms: 500,
+///     max_propose_ms: 4_000,
+///     precommit_ms: 1_000,
+///     precommit_delta_ms: 500,
+///     max_precommit_ms: 2_500,
+/// };
+/// assert_eq!(config.propose_timeout(0), 3_000);
+/// assert_eq!(config.propose_timeout(1), 3_500);
+/// assert_eq!(config.propose_timeout(10), 4_000);
+/// assert_eq!(config.precommit_timeout(2), 2_000);
+/// assert_eq!(config.precommit_timeout(10), 2_500);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutConfig {
+    /// Time to wait for the proposal of round 0, in milliseconds.
+    pub propose_ms: u64,
+    /// Time added to the timeout with every further round, in milliseconds.
+    pub propose_delta_ms: u64,
+    /// Longest propose timeout of any round, in milliseconds.
+    pub max_propose_ms: u64,
+    /// Time to wait for the remaining precommits of round 0, in milliseconds.
+    pub precommit_ms: u64,
+    /// Time added to the precommit timeout with every further round, in milliseconds.
+    pub precommit_delta_ms: u64,
+    /// Longest precommit timeout of any round, in milliseconds.
+    pub max_precommit_ms: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            propose_ms: 3_000,
+            propose_delta_ms: 1_000,
+            max_propose_ms: 60_000,
+            precommit_ms: 1_000,
+            precommit_delta_ms: 500,
+            max_precommit_ms: 30_000,
+        }
+    }
+}
+
+impl TimeoutConfig {
+    /// Returns how long to wait for the proposal of `round`, in milliseconds.
+    pub fn propose_timeout(&self, round: u32) -> u64 {
+        self.propose_delta_ms
+            .saturating_mul(round as u64)
+            .saturating_add(self.propose_ms)
+            .min(self.max_propose_ms)
+    }
+
+    /// Returns how long to wait for the remaining precommits of `round`, in milliseconds.
+    pub fn precommit_timeout(&self, round: u32) -> u64 {
+        self.precommit_delta_ms
+            .saturating_mul(round as u64)
+            .saturating_add(self.precommit_ms)
+            .min(self.max_precommit_ms)
+    }
+}
@@ -0,0 +1,233 @@
+// src/transaction.rs
+
+use crate::block::Hash;
+use crate::crypto::{Signature, SigningKey, VerifyingKey};
+use crate::ConsensusError;
+use ed25519_dalek::{Signer, Verifier};
+use serde::{Deserialize, Serialize};
+
+/// Domain prefix of the message a sender signs, so a transaction signature can never be replayed
+/// as a signature over anything else.
+const TRANSACTION_DOMAIN: &[u8] = b"peochain/transaction";
+
+/// Largest input accepted by an EVM call, in bytes.
+pub const MAX_CALL_DATA: usize = 32 * 1024;
+
+/// What a transaction does.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    /// Moves `amount` from the sender to account `to`.
+    Transfer {
+        /// Receiving account.
+        to: String,
+        /// Amount moved.
+        amount: u64,
+    },
+    /// Bonds `amount` of the sender's balance as stake behind validator `validator`.
+    Stake {
+        /// Validator the stake backs.
+        validator: String,
+        /// Amount bonded.
+        amount: u64,
+    },
+    /// Unbonds `amount` of the stake the sender bonded behind validator `validator`, returning it
+    /// to the sender's balance.
+    Unstake {
+        /// Validator the stake backed.
+        validator: String,
+        /// Amount unbonded.
+        amount: u64,
+    },
+    /// Calls contract `contract` with `data`, sending it `value`.
+    EvmCall {
+        /// Called contract account.
+        contract: String,
+        /// Amount sent along with the call.
+        value: u64,
+        /// Most gas the call may use.
+        gas_limit: u64,
+        /// Call input.
+        data: Vec<u8>,
+    },
+}
+
+impl TransactionKind {
+    /// Returns the amount the transaction takes from the sender's balance, fee excluded.
+    pub fn debit(&self) -> u64 {
+        match self {
+            TransactionKind::Transfer { amount, .. } | TransactionKind::Stake { amount, .. } => {
+                *amount
+            }
+            TransactionKind::Unstake { .. } => 0,
+            TransactionKind::EvmCall { value, .. } => *value,
+        }
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            TransactionKind::Transfer { to, amount } => {
+                out.push(0);
+                encode_str(out, to);
+                out.extend_from_slice(&amount.to_be_bytes());
+            }
+            TransactionKind::Stake { validator, amount } => {
+                out.push(1);
+                encode_str(out, validator);
+                out.extend_from_slice(&amount.to_be_bytes());
+            }
+            TransactionKind::Unstake { validator, amount } => {
+                out.push(2);
+                encode_str(out, validator);
+                out.extend_from_slice(&amount.to_be_bytes());
+            }
+            TransactionKind::EvmCall {
+                contract,
+                value,
+                gas_limit,
+                data,
+            } => {
+                out.push(3);
+                encode_str(out, contract);
+                out.extend_from_slice(&value.to_be_bytes());
+                out.extend_from_slice(&gas_limit.to_be_bytes());
+                out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+                out.extend_from_slice(data);
+            }
+        }
+    }
+}
+
+/// A signed instruction from an account, included in blocks.
+///
+/// A transaction is identified by the hash of its canonical encoding, signature included. The
+/// sender signs every other field, and its nonce must match the number of transactions it has
+/// had applied so far, so a transaction can be applied only once and only in order.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::crypto::SigningKey;
+/// use peo_consensus::transaction::{Transaction, TransactionKind};
+///
+/// let key = SigningKey::from_bytes(&[1; 32]);
+/// let kind = TransactionKind::Transfer { to: "bob".to_string(), amount: 25 };
+/// let mut tx = Transaction::new("alice".to_string(), 0, kind, 1);
+/// assert!(tx.check_stateless().is_err()); // Not signed yet.
+///
+/// tx.sign(&key);
+/// assert!(tx.check_stateless().is_ok());
+/// assert!(tx.verify_signature(&key.verifying_key()).is_ok());
+///
+/// let mut replayed = tx.clone();
+/// replayed.nonce = 1;
+/// assert!(replayed.verify_signature(&key.verifying_key()).is_err());
+/// assert_ne!(replayed.hash(), tx.hash());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Account sending the transaction.
+    pub sender: String,
+    /// Number of earlier transactions from the sender.
+    pub nonce: u64,
+    /// What the transaction does.
+    pub kind: TransactionKind,
+    /// Fee paid to the proposer of the block including the transaction.
+    pub fee: u64,
+    /// The sender's signature over the other fields, if the transaction has been signed.
+    pub signature: Option<Signature>,
+}
+
+impl Transaction {
+    /// Creates an unsigned transaction.
+    pub fn new(sender: String, nonce: u64, kind: TransactionKind, fee: u64) -> Self {
+        Self {
+            sender,
+            nonce,
+            kind,
+            fee,
+            signature: None,
+        }
+    }
+
+    /// Returns the canonical binary encoding of the transaction, signature included.
+    ///
+    /// Integers are big-endian, strings and byte strings are length-prefixed and the kind is
+    /// tagged, so two different transactions never share an encoding.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = self.encode_body();
+        match &self.signature {
+            Some(signature) => {
+                out.push(1);
+                out.extend_from_slice(&signature.to_bytes());
+            }
+            None => out.push(0),
+        }
+        out
+    }
+
+    /// Computes the canonical hash of the transaction, which identifies it.
+    pub fn hash(&self) -> Hash {
+        Hash::digest(&self.encode())
+    }
+
+    /// Returns the message the sender signs: a domain prefix followed by the encoding of every
+    /// field but the signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        [TRANSACTION_DOMAIN, &self.encode_body()].concat()
+    }
+
+    /// Signs the transaction with the sender's key.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = Some(key.sign(&self.signing_bytes()));
+    }
+
+    /// Checks that the transaction is signed by `key`.
+    pub fn verify_signature(&self, key: &VerifyingKey) -> Result<(), ConsensusError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(ConsensusError::InvalidSignature)?;
+        key.verify(&self.signing_bytes(), signature)
+            .map_err(|_| ConsensusError::InvalidSignature)
+    }
+
+    /// Checks everything that does not depend on account state: the transaction is signed, names
+    /// its accounts, moves a non-zero amount and, for an EVM call, has gas and bounded input.
+    pub fn check_stateless(&self) -> Result<(), ConsensusError> {
+        if self.signature.is_none() {
+            return Err(ConsensusError::InvalidSignature);
+        }
+        let well_formed = !self.sender.is_empty()
+            && match &self.kind {
+                TransactionKind::Transfer { to, amount } => !to.is_empty() && *amount > 0,
+                TransactionKind::Stake { validator, amount }
+                | TransactionKind::Unstake { validator, amount } => {
+                    !validator.is_empty() && *amount > 0
+                }
+                TransactionKind::EvmCall {
+                    contract,
+                    gas_limit,
+                    data,
+                    ..
+                } => !contract.is_empty() && *gas_limit > 0 && data.len() <= MAX_CALL_DATA,
+            };
+        if !well_formed {
+            return Err(ConsensusError::InvalidTransaction);
+        }
+        Ok(())
+    }
+
+    fn encode_body(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + self.sender.len());
+        encode_str(&mut out, &self.sender);
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&self.fee.to_be_bytes());
+        self.kind.encode_into(&mut out);
+        out
+    }
+}
+
+fn encode_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
@@ -0,0 +1,294 @@
+// src/vote.rs
+
+use crate::block::Hash;
+use crate::crypto::{Signature, SigningKey, ValidatorRegistry};
+use crate::ConsensusError;
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Domain prefix of the message a validator signs when voting.
+const VOTE_DOMAIN: &[u8] = b"peochain/vote";
+
+/// The two voting steps a validator goes through for every proposal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoteType {
+    /// First step: the validator judged the proposal valid (or not).
+    Prevote,
+    /// Second step: the validator saw a supermajority of prevotes and commits to the block.
+    Precommit,
+}
+
+/// A validator's vote for a block, or for nil, at a given height and round.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Vote {
+    /// Which voting step this vote belongs to.
+    pub vote_type: VoteType,
+    /// Height of the block being voted on.
+    pub height: u64,
+    /// Round within the height in which the vote was cast.
+    pub round: u32,
+    /// Hash of the block voted for, or `None` for a nil vote.
+    pub block_hash: Option<Hash>,
+    /// Identifier of the voting validator.
+    pub validator: String,
+    /// The validator's signature over the vote, if it has been signed.
+    pub signature: Option<Signature>,
+}
+
+impl Vote {
+    /// Returns whether this is a vote against every block.
+    pub fn is_nil(&self) -> bool {
+        self.block_hash.is_none()
+    }
+
+    /// Returns the canonical message a validator signs for this vote.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(VOTE_DOMAIN.len() + 1 + 8 + 4 + 33 + 4 + self.validator.len());
+        out.extend_from_slice(VOTE_DOMAIN);
+        out.push(match self.vote_type {
+            VoteType::Prevote => 0,
+            VoteType::Precommit => 1,
+        });
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(&self.round.to_be_bytes());
+        match &self.block_hash {
+            Some(hash) => {
+                out.push(1);
+                out.extend_from_slice(hash.as_bytes());
+            }
+            None => out.push(0),
+        }
+        out.extend_from_slice(&(self.validator.len() as u32).to_be_bytes());
+        out.extend_from_slice(self.validator.as_bytes());
+        out
+    }
+
+    /// Signs the vote with the validator's key.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = Some(key.sign(&self.signing_bytes()));
+    }
+
+    /// Checks that the vote is signed by the key registered for its validator.
+    pub fn verify_signature(&self, registry: &ValidatorRegistry) -> Result<(), ConsensusError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(ConsensusError::InvalidSignature)?;
+        registry.verify(&self.validator, &self.signing_bytes(), signature)
+    }
+}
+
+/// How much each validator's vote counts towards a quorum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoteWeighting {
+    /// Votes are weighted by own and delegated stake alone.
+    #[default]
+    Stake,
+    /// Votes are weighted like proposer selection: synergy score plus a fraction of own and
+    /// delegated stake, as set by the scoring policy.
+    StakeAndSynergy,
+}
+
+/// Returns whether `weight` is strictly more than two thirds of `total`.
+pub fn has_supermajority(weight: f64, total: f64) -> bool {
+    weight * 3.0 > total * 2.0
+}
+
+/// Collects the votes of one type for a single height and round, together with their weight.
+///
+/// Only the first vote of each validator is counted.
+///
+/// # Examples
+///
+/// ```rust
+/// use peo_consensus::{Hash, Vote, VoteSet, VoteType};
+///
+/// let hash = Hash::digest(b"block");
+/// let vote = |validator: &str, block_hash| Vote {
+///     vote_type: VoteType::Prevote,
+///     height: 1,
+///     round: 0,
+///     block_hash,
+///     validator: validator.to_string(),
+///     signature: None,
+/// };
+///
+/// let mut votes = VoteSet::new(VoteType::Prevote, 1, 0, 300.0);
+/// votes.add(vote("v1", Some(hash)), 100.0);
+/// votes.add(vote("v2", Some(hash)), 100.0);
+/// assert_eq!(votes.quorum(), None);
+/// votes.add(vote("v3", Some(hash)), 100.0);
+/// assert_eq!(votes.quorum(), Some(Some(hash)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct VoteSet {
+    vote_type: VoteType,
+    height: u64,
+    round: u32,
+    total_weight: f64,
+    votes: Vec<(Vote, f64)>,
+    weight_by_value: HashMap<Option<Hash>, f64>,
+}
+
+impl VoteSet {
+    /// Creates an empty vote set where `total_weight` is the weight of the whole validator set.
+    pub fn new(vote_type: VoteType, height: u64, round: u32, total_weight: f64) -> Self {
+        Self {
+            vote_type,
+            height,
+            round,
+            total_weight,
+            votes: Vec::new(),
+            weight_by_value: HashMap::new(),
+        }
+    }
+
+    /// Adds a vote carrying `weight`.
+    ///
+    /// Returns `false`, without counting it, if the vote belongs to another step, height or round,
+    /// or if the validator has already voted in this set.
+    pub fn add(&mut self, vote: Vote, weight: f64) -> bool {
+        if vote.vote_type != self.vote_type
+            || vote.height != self.height
+            || vote.round != self.round
+        {
+            return false;
+        }
+        if self
+            .votes
+            .iter()
+            .any(|(v, _)| v.validator == vote.validator)
+        {
+            return false;
+        }
+        *self.weight_by_value.entry(vote.block_hash).or_insert(0.0) += weight;
+        self.votes.push((vote, weight));
+        true
+    }
+
+    /// Returns the total weight of votes for `block_hash` (`None` for nil).
+    pub fn weight_for(&self, block_hash: Option<Hash>) -> f64 {
+        self.weight_by_value
+            .get(&block_hash)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the value, a block hash or nil, that gathered more than two thirds of the weight.
+    pub fn quorum(&self) -> Option<Option<Hash>> {
+        self.weight_by_value
+            .iter()
+            .find(|(_, weight)| has_supermajority(**weight, self.total_weight))
+            .map(|(value, _)| *value)
+    }
+
+    /// Returns the weight of every vote collected so far, whatever its value.
+    pub fn cast_weight(&self) -> f64 {
+        self.votes.iter().map(|(_, weight)| weight).sum()
+    }
+
+    /// Returns the weight of the whole validator set.
+    pub fn total_weight(&self) -> f64 {
+        self.total_weight
+    }
+
+    /// Returns the votes collected so far.
+    pub fn votes(&self) -> impl Iterator<Item = &Vote> {
+        self.votes.iter().map(|(vote, _)| vote)
+    }
+
+    /// Builds a certificate for `block_hash` if it gathered a supermajority of precommits.
+    pub fn certificate(&self, block_hash: Hash) -> Option<QuorumCertificate> {
+        let signed_weight = self.weight_for(Some(block_hash));
+        if self.vote_type != VoteType::Precommit
+            || !has_supermajority(signed_weight, self.total_weight)
+        {
+            return None;
+        }
+        Some(QuorumCertificate {
+            height: self.height,
+            round: self.round,
+            block_hash,
+            precommits: self
+                .votes()
+                .filter(|vote| vote.block_hash == Some(block_hash))
+                .cloned()
+                .collect(),
+            signed_weight,
+            total_weight: self.total_weight,
+        })
+    }
+}
+
+/// The precommits that finalized a block, claimed to carry more than two thirds of the voting
+/// weight.
+///
+/// `signed_weight` and `total_weight` are reported by whoever built the certificate, so they prove
+/// nothing on their own. A certificate received from elsewhere proves the block final only once
+/// [`QuorumCertificate::verify`] has checked, with the validators' public keys and stakes, that
+/// distinct validators holding more than two thirds of the stake signed it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuorumCertificate {
+    /// Height of the finalized block.
+    pub height: u64,
+    /// Round in which the block was finalized.
+    pub round: u32,
+    /// Hash of the finalized block.
+    pub block_hash: Hash,
+    /// The precommits for the block.
+    pub precommits: Vec<Vote>,
+    /// Combined weight of the precommits.
+    pub signed_weight: f64,
+    /// Weight of the whole validator set when the block was finalized.
+    pub total_weight: f64,
+}
+
+impl QuorumCertificate {
+    /// Checks that every precommit in the certificate is signed by its validator and is for the
+    /// certified block, height and round.
+    pub fn verify_signatures(&self, registry: &ValidatorRegistry) -> Result<(), ConsensusError> {
+        for vote in &self.precommits {
+            if vote.vote_type != VoteType::Precommit
+                || vote.height != self.height
+                || vote.round != self.round
+                || vote.block_hash != Some(self.block_hash)
+            {
+                return Err(ConsensusError::InvalidBlock);
+            }
+            vote.verify_signature(registry)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the certificate proves its block final under the validator set `stakes`, which
+    /// maps every validator to its stake.
+    ///
+    /// Besides the checks of [`verify_signatures`](Self::verify_signatures), every signer must be
+    /// a member of the set and sign only once, and the stake of the signers, recomputed from
+    /// `stakes` rather than taken from `signed_weight`, must be more than two thirds of the total.
+    pub fn verify(
+        &self,
+        registry: &ValidatorRegistry,
+        stakes: &HashMap<String, u64>,
+    ) -> Result<(), ConsensusError> {
+        self.verify_signatures(registry)?;
+        let mut signers = HashSet::new();
+        let mut signed = 0;
+        for vote in &self.precommits {
+            let stake = stakes
+                .get(&vote.validator)
+                .ok_or(ConsensusError::UnknownValidator)?;
+            if !signers.insert(vote.validator.as_str()) {
+                return Err(ConsensusError::InvalidBlock);
+            }
+            signed += stake;
+        }
+        let total: u64 = stakes.values().sum();
+        if !has_supermajority(signed as f64, total as f64) {
+            return Err(ConsensusError::InvalidBlock);
+        }
+        Ok(())
+    }
+}